@@ -0,0 +1,42 @@
+use std::{env, fs, path::Path};
+
+/// Find the version of the package resolved in the content of Cargo.lock.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    lock.split("[[package]]").find_map(|package| {
+        let mut lines = package.lines().map(str::trim);
+        lines.find(|line| *line == name_line)?;
+        lines
+            .find_map(|line| line.strip_prefix("version = \""))
+            .map(|version| version.trim_end_matches('"').to_string())
+    })
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    // Cargo.lock is next to the manifest or in the root of a workspace
+    let lock = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.exists());
+    if let Some(lock) = &lock {
+        println!("cargo:rerun-if-changed={}", lock.display());
+    }
+    let arrow_version = lock
+        .and_then(|lock| fs::read_to_string(lock).ok())
+        .and_then(|lock| locked_version(&lock, "arrow"))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=FALSA_ARROW_VERSION={}", arrow_version);
+
+    // default only groups other features, so it is not reported
+    let mut features = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .filter(|feature| feature != "default")
+        .collect::<Vec<String>>();
+    features.sort();
+    println!("cargo:rustc-env=FALSA_FEATURES={}", features.join(","));
+    println!("cargo:rerun-if-changed=build.rs");
+}
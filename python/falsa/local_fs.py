@@ -29,6 +29,14 @@ def _validate_int64(num: int, prefix: str) -> None:
         raise ValueError(f"Values are passed to native as int64; MAX={NATIVE_I64_MAX_VALUE} but got {prefix}={num}")
 
 
+def _batch_seed(seed: int | None, index: int) -> int | None:
+    # Own seeds of columns are derived per batch, otherwise every batch repeats
+    # the values of the first one; the first batch keeps the seed as passed.
+    if (seed is None) or (index == 0):
+        return seed
+    return random.Random(f"{seed}:{index}").randint(0, NATIVE_I64_MAX_VALUE)
+
+
 class GroupByGenerator:
     """A simple wrapper on top of native generator.
 
@@ -37,18 +45,173 @@ class GroupByGenerator:
     """
 
     def __init__(
-        self, size: H2ODatasetSizes | int, k: int, nas: int = 0, seed: int = 42, batch_size: int = 5_000_000
+        self,
+        size: H2ODatasetSizes | int,
+        k: int,
+        nas: int = 0,
+        seed: int = 42,
+        batch_size: int = 5_000_000,
+        v3_round: int | None = None,
+        with_map: bool = False,
+        nas_string: int | None = None,
+        nas_int: int | None = None,
+        shuffle: bool = False,
+        with_duration: bool = False,
+        dur_min: int = 0,
+        dur_max: int = 86_400,
+        key_stride: int = 1,
+        v3_nan_rate: float = 0.0,
+        monotonic_ts: bool = False,
+        ts_start: int = 0,
+        hex_ids: bool = False,
+        unique_id3: bool = False,
+        id4_id5_corr: float = 0.0,
+        blob_bytes: int = 0,
+        feature_dim: int = 0,
+        fast_nulls: bool = False,
+        id2_values: list[int] | None = None,
+        force_nullable: bool = False,
+        key_dist: str = "uniform",
+        key_dist_shape: float = 1.0,
+        nk_round: str = "floor",
+        with_time: bool = False,
+        column_seeds: dict[str, int] | None = None,
+        strict_width: bool = False,
+        float16: bool = False,
+        null_sentinel: str | None = None,
+        null_bias_groups: list[int] | None = None,
+        null_bias_nas: int = 50,
+        id1_offset: int = 0,
+        corrupt_rate: float = 0.0,
+        corrupt_target: str | None = None,
+        cumulative_v1: bool = False,
+        exact_percent: bool = True,
+        v3_edge_rate: float = 0.0,
+        sort_by: list[str] | None = None,
+        key_seed: int | None = None,
+        value_seed: int | None = None,
+        binary_id3: bool = False,
+        run_length: int = 1,
+        v3_collision_levels: int | None = None,
+        seasonal: bool = False,
+        column_order: list[str] | None = None,
+        max_memory_bytes: int | None = None,
+        index_hashed_keys: bool = False,
+        decimal256: bool = False,
+        with_offset: bool = False,
+        filter: str | None = None,
+        with_checksum: bool = False,
+        target_compressibility: float = 0.0,
+        field_metadata: dict[str, dict[str, str]] | None = None,
+        messy_strings: bool = False,
+        messy_rate: float = 0.1,
+        group_sizes: list[int] | None = None,
+        with_string_list: bool = False,
     ) -> None:
         _validate_int64(size, "size")
         if (nas < 0) or (nas > 100):
             raise ValueError(f"nas should be in [0, 100], but got {nas}")
+        if (nas_string is not None) and ((nas_string < 0) or (nas_string > 100)):
+            raise ValueError(f"nas_string should be in [0, 100], but got {nas_string}")
+        if (nas_int is not None) and ((nas_int < 0) or (nas_int > 100)):
+            raise ValueError(f"nas_int should be in [0, 100], but got {nas_int}")
+        if dur_min > dur_max:
+            raise ValueError(f"dur_min should be less or equal than dur_max, but got {dur_min} > {dur_max}")
+        if key_stride <= 0:
+            raise ValueError(f"key_stride should be positive, but got {key_stride}")
+        if (v3_nan_rate < 0.0) or (v3_nan_rate > 1.0):
+            raise ValueError(f"v3_nan_rate should be in [0, 1], but got {v3_nan_rate}")
+        if (v3_edge_rate < 0.0) or (v3_edge_rate > 1.0):
+            raise ValueError(f"v3_edge_rate should be in [0, 1], but got {v3_edge_rate}")
+        if (id4_id5_corr < 0.0) or (id4_id5_corr > 1.0):
+            raise ValueError(f"id4_id5_corr should be in [0, 1], but got {id4_id5_corr}")
+        if (null_bias_nas < 0) or (null_bias_nas > 100):
+            raise ValueError(f"null_bias_nas should be in [0, 100], but got {null_bias_nas}")
+        if (messy_rate < 0.0) or (messy_rate > 1.0):
+            raise ValueError(f"messy_rate should be in [0, 1], but got {messy_rate}")
+        if (target_compressibility < 0.0) or (target_compressibility > 1.0):
+            raise ValueError(f"target_compressibility should be in [0, 1], but got {target_compressibility}")
+        if (corrupt_rate < 0.0) or (corrupt_rate > 1.0):
+            raise ValueError(f"corrupt_rate should be in [0, 1], but got {corrupt_rate}")
+        if (sort_by is not None) and shuffle:
+            raise ValueError("sort_by is not compatible with shuffle")
+        if cumulative_v1 and shuffle:
+            raise ValueError("cumulative_v1 is not compatible with shuffle")
+        if id1_offset < 0:
+            raise ValueError(f"id1_offset should be non-negative, but got {id1_offset}")
+        if run_length < 1:
+            raise ValueError(f"run_length should be positive, but got {run_length}")
+        if seasonal and not monotonic_ts:
+            raise ValueError("seasonal requires monotonic_ts")
+        if seasonal and (v3_collision_levels is not None):
+            raise ValueError("seasonal is not compatible with v3_collision_levels")
+        if (v3_collision_levels is not None) and (v3_collision_levels <= 0):
+            raise ValueError(f"v3_collision_levels should be positive, but got {v3_collision_levels}")
         if (k < 0) or (k > size):
             raise ValueError(f"k should be positive and less than {size} but got {k}")
         if (batch_size <= 0) or (batch_size > size):
             raise ValueError(f"batch size should be positive and less than {size} but got {batch_size}")
+        if (group_sizes is not None) and (batch_size < size):
+            raise ValueError(f"group_sizes requires a single batch, so batch_size should be {size}, but got {batch_size}")
         self.n: int = size
         self.k = k
         self.nas = nas
+        self.v3_round = v3_round
+        self.with_map = with_map
+        self.nas_string = nas_string
+        self.nas_int = nas_int
+        self.shuffle = shuffle
+        self.with_duration = with_duration
+        self.dur_min = dur_min
+        self.dur_max = dur_max
+        self.key_stride = key_stride
+        self.v3_nan_rate = v3_nan_rate
+        self.monotonic_ts = monotonic_ts
+        self.ts_start = ts_start
+        self.hex_ids = hex_ids
+        self.unique_id3 = unique_id3
+        self.id4_id5_corr = id4_id5_corr
+        self.blob_bytes = blob_bytes
+        self.feature_dim = feature_dim
+        self.fast_nulls = fast_nulls
+        self.id2_values = id2_values
+        self.force_nullable = force_nullable
+        self.key_dist = key_dist
+        self.key_dist_shape = key_dist_shape
+        self.nk_round = nk_round
+        self.with_time = with_time
+        self.column_seeds = column_seeds
+        self.strict_width = strict_width
+        self.float16 = float16
+        self.null_sentinel = null_sentinel
+        self.null_bias_groups = null_bias_groups
+        self.null_bias_nas = null_bias_nas
+        self.id1_offset = id1_offset
+        self.corrupt_rate = corrupt_rate
+        self.corrupt_target = corrupt_target
+        self.cumulative_v1 = cumulative_v1
+        self.exact_percent = exact_percent
+        self.v3_edge_rate = v3_edge_rate
+        self.sort_by = sort_by
+        self.key_seed = key_seed
+        self.value_seed = value_seed
+        self.binary_id3 = binary_id3
+        self.run_length = run_length
+        self.v3_collision_levels = v3_collision_levels
+        self.seasonal = seasonal
+        self.column_order = column_order
+        self.max_memory_bytes = max_memory_bytes
+        self.index_hashed_keys = index_hashed_keys
+        self.decimal256 = decimal256
+        self.with_offset = with_offset
+        self.filter = filter
+        self.with_checksum = with_checksum
+        self.target_compressibility = target_compressibility
+        self.field_metadata = field_metadata
+        self.messy_strings = messy_strings
+        self.messy_rate = messy_rate
+        self.group_sizes = group_sizes
+        self.with_string_list = with_string_list
 
         num_batches = self.n // batch_size
         batches = [batch_size for _ in range(num_batches)]
@@ -61,8 +224,80 @@ class GroupByGenerator:
         self.batches = [{"size": bs, "seed": random.randint(0, NATIVE_I64_MAX_VALUE)} for bs in batches]
 
     def iter_batches(self) -> Iterator[pa.RecordBatch]:
-        for batch in self.batches:
-            yield generate_groupby(self.n, self.k, self.nas, batch["seed"], batch["size"])
+        ts_start = self.ts_start
+        for index, batch in enumerate(self.batches):
+            rb = generate_groupby(
+                self.n,
+                self.k,
+                self.nas,
+                batch["seed"],
+                batch["size"],
+                v3_round=self.v3_round,
+                with_map=self.with_map,
+                nas_string=self.nas_string,
+                nas_int=self.nas_int,
+                shuffle=self.shuffle,
+                with_duration=self.with_duration,
+                dur_min=self.dur_min,
+                dur_max=self.dur_max,
+                key_stride=self.key_stride,
+                v3_nan_rate=self.v3_nan_rate,
+                monotonic_ts=self.monotonic_ts,
+                ts_start=ts_start,
+                hex_ids=self.hex_ids,
+                unique_id3=self.unique_id3,
+                id4_id5_corr=self.id4_id5_corr,
+                blob_bytes=self.blob_bytes,
+                feature_dim=self.feature_dim,
+                fast_nulls=self.fast_nulls,
+                id2_values=self.id2_values,
+                force_nullable=self.force_nullable,
+                key_dist=self.key_dist,
+                key_dist_shape=self.key_dist_shape,
+                nk_round=self.nk_round,
+                with_time=self.with_time,
+                column_seeds=(
+                    None
+                    if self.column_seeds is None
+                    else {name: _batch_seed(seed, index) for name, seed in self.column_seeds.items()}
+                ),
+                strict_width=self.strict_width,
+                float16=self.float16,
+                null_sentinel=self.null_sentinel,
+                null_bias_groups=self.null_bias_groups,
+                null_bias_nas=self.null_bias_nas,
+                id1_offset=self.id1_offset,
+                corrupt_rate=self.corrupt_rate,
+                corrupt_target=self.corrupt_target,
+                cumulative_v1=self.cumulative_v1,
+                exact_percent=self.exact_percent,
+                v3_edge_rate=self.v3_edge_rate,
+                sort_by=self.sort_by,
+                key_seed=_batch_seed(self.key_seed, index),
+                value_seed=_batch_seed(self.value_seed, index),
+                binary_id3=self.binary_id3,
+                run_length=self.run_length,
+                v3_collision_levels=self.v3_collision_levels,
+                seasonal=self.seasonal,
+                column_order=self.column_order,
+                max_memory_bytes=self.max_memory_bytes,
+                index_hashed_keys=self.index_hashed_keys,
+                decimal256=self.decimal256,
+                with_offset=self.with_offset,
+                filter=self.filter,
+                with_checksum=self.with_checksum,
+                target_compressibility=self.target_compressibility,
+                field_metadata=self.field_metadata,
+                messy_strings=self.messy_strings,
+                messy_rate=self.messy_rate,
+                group_sizes=self.group_sizes,
+                with_string_list=self.with_string_list,
+            )
+            if self.monotonic_ts and (rb.num_rows > 0):
+                # Carry the last timestamp to keep ts strictly increasing across batches,
+                # a batch without rows (for example after filter) keeps the previous start
+                ts_start = int(rb.column("ts").cast(pa.int64()).to_numpy().max()) + 1
+            yield rb
 
 
 class JoinGenerator(ABC):
@@ -75,6 +310,14 @@ class JoinGenerator(ABC):
         seed: int = 42,
         keys_seed: int = 142,
         batch_size: int = 5_000_000,
+        sort_by_key: bool = False,
+        dict_keys: bool = False,
+        int_measure: bool = False,
+        zero_based_keys: bool = False,
+        disjoint: bool = False,
+        measure_min: float = 1.0,
+        measure_max: float = 100.0,
+        measure_dist: str = "uniform",
     ) -> None:
         _validate_int64(size, "size")
         if (k < 0) or (k > size):
@@ -86,6 +329,14 @@ class JoinGenerator(ABC):
         self.k = k
         self.keys_seed = keys_seed
         self.nas = nas
+        self.sort_by_key = sort_by_key
+        self.dict_keys = dict_keys
+        self.int_measure = int_measure
+        self.zero_based_keys = zero_based_keys
+        self.disjoint = disjoint
+        self.measure_min = measure_min
+        self.measure_max = measure_max
+        self.measure_dist = measure_dist
 
         self.kk1 = self.generate_keys(int(self.n / 1e6))
         self.kk2 = self.generate_keys(int(self.n / 1e3))
@@ -105,7 +356,8 @@ class JoinGenerator(ABC):
     def generate_keys(self, nn: int) -> dict[str, np.ndarray]:
         total_size = int(nn * 1.1)
         npr = np.random.default_rng(seed=self.keys_seed)
-        key = npr.permutation(total_size) + 1
+        # Keys are from 1 to total_size as in h2o, or from 0 if zero-based keys are requested
+        key = npr.permutation(total_size) + (0 if self.zero_based_keys else 1)
 
         x_end = int(nn * 0.9)
         l_end = int(nn * 1.1)
@@ -144,7 +396,17 @@ class JoinSmallGenerator(JoinGenerator):
             _t = prev_batch
             prev_batch += batch["size"]
             yield generate_join_rhs_small(
-                self.n, batch["seed"], pa.array(self.k1[_t:prev_batch], type=pa.int64()), batch["size"]
+                self.n,
+                batch["seed"],
+                pa.array(self.k1[_t:prev_batch], type=pa.int64()),
+                batch["size"],
+                sort_by_key=self.sort_by_key,
+                dict_keys=self.dict_keys,
+                int_measure=self.int_measure,
+                disjoint=self.disjoint,
+                measure_min=self.measure_min,
+                measure_max=self.measure_max,
+                measure_dist=self.measure_dist,
             )
 
 
@@ -170,6 +432,13 @@ class JoinMediumGenerator(JoinGenerator):
                 pa.array(self.k1[_t:prev_batch], type=pa.int64()),
                 pa.array(self.k2[_t:prev_batch], type=pa.int64()),
                 batch["size"],
+                sort_by_key=self.sort_by_key,
+                dict_keys=self.dict_keys,
+                int_measure=self.int_measure,
+                disjoint=self.disjoint,
+                measure_min=self.measure_min,
+                measure_max=self.measure_max,
+                measure_dist=self.measure_dist,
             )
 
 
@@ -197,6 +466,13 @@ class JoinBigGenerator(JoinGenerator):
                 pa.array(self.k2[_t:prev_batch], type=pa.int64()),
                 pa.array(self.k3[_t:prev_batch], type=pa.int64()),
                 batch["size"],
+                sort_by_key=self.sort_by_key,
+                dict_keys=self.dict_keys,
+                int_measure=self.int_measure,
+                disjoint=self.disjoint,
+                measure_min=self.measure_min,
+                measure_max=self.measure_max,
+                measure_dist=self.measure_dist,
             )
 
 
@@ -224,4 +500,10 @@ class JoinLHSGenerator(JoinGenerator):
                 pa.array(self.k2[_t:prev_batch], type=pa.int64()),
                 pa.array(self.k3[_t:prev_batch], type=pa.int64()),
                 batch["size"],
+                sort_by_key=self.sort_by_key,
+                dict_keys=self.dict_keys,
+                int_measure=self.int_measure,
+                measure_min=self.measure_min,
+                measure_max=self.measure_max,
+                measure_dist=self.measure_dist,
             )
@@ -8,16 +8,56 @@ A partial inspirations for the Rust code:
 
 */
 use arrow::{
-    array::{ArrayData, Float64Builder, Int64Array, Int64Builder, RecordBatch, StringBuilder},
-    datatypes::{DataType, Field, Schema},
+    array::{
+        make_array, Array, ArrayData, ArrayRef, AsArray, BinaryBuilder, BooleanArray,
+        Decimal256Builder, DictionaryArray, DurationSecondBuilder, FixedSizeListBuilder,
+        Float16Builder, Float64Array, Float64Builder, Int32Array, Int64Array, Int64Builder,
+        LargeBinaryBuilder, ListBuilder, MapBuilder, RecordBatch, Scalar, StringArray,
+        StringBuilder, Time64MicrosecondBuilder, TimestampMicrosecondBuilder, UInt32Array,
+    },
+    buffer::{BooleanBuffer, NullBuffer},
+    compute::{
+        cast, concat_batches, filter_record_batch, kernels::zip::zip, lexsort_to_indices, take,
+        take_record_batch, SortColumn, SortOptions,
+    },
+    datatypes::{
+        i256, DataType, Field, Float64Type, Int32Type, Int64Type, Schema, SchemaRef, TimeUnit,
+        TimestampMicrosecondType,
+    },
+    ffi::to_ffi,
+    ipc::{
+        writer::{FileWriter, IpcWriteOptions},
+        MetadataVersion,
+    },
     pyarrow::PyArrowType,
+    record_batch::{RecordBatchOptions, RecordBatchReader},
 };
-use pyo3::exceptions::PyValueError;
+use half::f16;
+use object_store::ObjectStore;
+use parquet::arrow::{
+    arrow_reader::ParquetRecordBatchReaderBuilder, async_writer::ParquetObjectWriter, ArrowWriter,
+    AsyncArrowWriter,
+};
+#[cfg(feature = "zstd")]
+use parquet::basic::ZstdLevel;
+use parquet::basic::{Compression, GzipLevel};
+use parquet::file::properties::WriterProperties;
+use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use rand::distr::Uniform;
-use rand::{distr::Distribution, SeedableRng};
+use rand::seq::SliceRandom;
+use rand::{distr::Distribution, Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use rand_distr::{Normal, Pareto};
+use serde_json::json;
+use shared_memory::ShmemConf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use url::Url;
 
 #[derive(Debug)]
 struct UniformError(rand::distr::uniform::Error);
@@ -27,133 +67,4218 @@ impl From<UniformError> for PyErr {
     }
 }
 
+#[derive(Debug)]
+struct ArrowError(arrow::error::ArrowError);
+impl From<ArrowError> for PyErr {
+    fn from(error: ArrowError) -> Self {
+        PyErr::new::<PyValueError, _>(format!("{}", error.0))
+    }
+}
+
+#[derive(Debug)]
+struct ParquetError(parquet::errors::ParquetError);
+impl From<ParquetError> for PyErr {
+    fn from(error: ParquetError) -> Self {
+        PyErr::new::<PyIOError, _>(format!("{}", error.0))
+    }
+}
+
+#[derive(Debug)]
+struct ObjectStoreError(object_store::Error);
+impl From<ObjectStoreError> for PyErr {
+    fn from(error: ObjectStoreError) -> Self {
+        PyErr::new::<PyIOError, _>(format!("{}", error.0))
+    }
+}
+
+/// An amount of digits of the positive value in the given radix.
+fn num_digits(value: i64, radix: i64) -> usize {
+    let mut digits = 1;
+    let mut rest = value / radix;
+    while rest > 0 {
+        digits += 1;
+        rest /= radix;
+    }
+    digits
+}
+
+/// Sample an integer key from 1 to upper, uniformly or from the pareto tail if passed.
+fn sample_key(
+    pareto: Option<&Pareto<f64>>,
+    uniform: &Uniform<i64>,
+    upper: i64,
+    rng: &mut ChaCha8Rng,
+) -> i64 {
+    match pareto {
+        Some(pareto) => (pareto.sample(rng) as i64).min(upper),
+        None => uniform.sample(rng),
+    }
+}
+
+/// Draw a value of a column with draw from the main stream and, if the column has its own
+/// stream, from it too with own_arg instead of main_arg. The main stream makes the same draws
+/// as without the own stream, so other columns are not shifted by it.
+/// Returns the value drawn from the main stream and the value of the output.
+fn draw_column<A, T: Clone>(
+    own: Option<&mut ChaCha8Rng>,
+    main: &mut ChaCha8Rng,
+    main_arg: A,
+    own_arg: A,
+    mut draw: impl FnMut(&mut ChaCha8Rng, A) -> T,
+) -> (T, T) {
+    let main_value = draw(main, main_arg);
+    match own {
+        Some(own) => (main_value, draw(own, own_arg)),
+        None => (main_value.clone(), main_value),
+    }
+}
+
+/// Replace the validity of the array by one sampled in a single pass with respect to nas.
+fn with_sampled_nulls(
+    array: &ArrayRef,
+    nas: i64,
+    distr_nas: &Uniform<i64>,
+    rng: &mut ChaCha8Rng,
+) -> Result<ArrayRef, arrow::error::ArrowError> {
+    if nas == 0 {
+        return Ok(array.clone());
+    }
+    let validity = BooleanBuffer::collect_bool(array.len(), |_| distr_nas.sample(rng) >= nas);
+    let data = array
+        .to_data()
+        .into_builder()
+        .nulls(Some(NullBuffer::new(validity)))
+        .build()?;
+    Ok(make_array(data))
+}
+
+/// Encode string key columns id4, id5 and id6 of the join batch as Dictionary(Int32, Utf8).
+/// All the columns share a single dictionary: sorted distinct values of these columns.
+fn dictionary_encode_keys(batch: &RecordBatch) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let schema = batch.schema();
+    let indices = ["id4", "id5", "id6"]
+        .iter()
+        .filter_map(|name| schema.index_of(name).ok())
+        .collect::<Vec<usize>>();
+    let mut values = indices
+        .iter()
+        .flat_map(|index| batch.column(*index).as_string::<i32>().iter().flatten())
+        .collect::<Vec<&str>>();
+    values.sort_unstable();
+    values.dedup();
+    if values.len() > i32::MAX as usize {
+        return Err(arrow::error::ArrowError::DictionaryKeyOverflowError);
+    }
+    let codes = values
+        .iter()
+        .enumerate()
+        .map(|(code, value)| (*value, code as i32))
+        .collect::<HashMap<&str, i32>>();
+    let dictionary: ArrayRef = Arc::new(StringArray::from_iter_values(values));
+
+    let mut fields = schema
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect::<Vec<Field>>();
+    let mut columns = batch.columns().to_vec();
+    for index in indices {
+        let keys = batch
+            .column(index)
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(|value| codes[value]))
+            .collect::<Int32Array>();
+        let encoded = DictionaryArray::<Int32Type>::try_new(keys, dictionary.clone())?;
+        fields[index] = fields[index]
+            .clone()
+            .with_data_type(encoded.data_type().clone());
+        columns[index] = Arc::new(encoded);
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// Encode a column of idNNN strings as Dictionary(Int32, Utf8) with the given dictionary
+/// of all the values for 1..=k, so codes are the same in every batch: code = NNN - 1.
+fn encode_with_dictionary(
+    batch: &RecordBatch,
+    name: &str,
+    dictionary: &ArrayRef,
+) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let index = batch.schema().index_of(name)?;
+    let keys = batch
+        .column(index)
+        .as_string::<i32>()
+        .iter()
+        .map(|value| {
+            value
+                .and_then(|value| value[2..].parse::<i32>().ok())
+                .map(|number| number - 1)
+        })
+        .collect::<Int32Array>();
+    let encoded = DictionaryArray::<Int32Type>::try_new(keys, dictionary.clone())?;
+
+    let schema = batch.schema();
+    let mut fields = schema
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect::<Vec<Field>>();
+    fields[index] = fields[index]
+        .clone()
+        .with_data_type(encoded.data_type().clone());
+    let mut columns = batch.columns().to_vec();
+    columns[index] = Arc::new(encoded);
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// A capacity in bytes for the values of a string or binary builder.
+/// Saturates instead of overflowing, so huge batches do not panic or under-allocate.
+/// It is also capped by i32::MAX, the maximal size of values of a Utf8 array,
+/// builders grow on demand beyond the reserved capacity.
+fn safe_string_capacity(items: usize, per_item: usize) -> usize {
+    items.saturating_mul(per_item).min(i32::MAX as usize)
+}
+
+/// Generate the measure column of join tables from [measure_min, measure_max]: Float64
+/// or Int64 if int_measure is true. With "uniform" values are uniform, with "normal" they are
+/// normal around the middle of the range with a sixth of the range as the standard deviation
+/// and clamped to the range (integers are rounded).
+fn join_measure(
+    int_measure: bool,
+    measure_min: f64,
+    measure_max: f64,
+    measure_dist: &str,
+    size: usize,
+    rng: &mut ChaCha8Rng,
+) -> PyResult<ArrayRef> {
+    if !(measure_min.is_finite() && measure_max.is_finite() && (measure_min <= measure_max)) {
+        return Err(PyValueError::new_err(format!(
+            "measure_min and measure_max should be finite and measure_min <= measure_max, but got {} and {}",
+            measure_min, measure_max
+        )));
+    }
+    let (int_min, int_max) = (measure_min.ceil(), measure_max.floor());
+    if int_measure && (int_min > int_max) {
+        return Err(PyValueError::new_err(format!(
+            "[{}, {}] should contain an integer for int_measure",
+            measure_min, measure_max
+        )));
+    }
+    match (measure_dist, int_measure) {
+        ("uniform", true) => {
+            let distr_int = Uniform::<i64>::try_from(int_min as i64..=int_max as i64)
+                .map_err(|e| UniformError(e))?;
+            let values = (0..size)
+                .map(|_| distr_int.sample(rng))
+                .collect::<Int64Array>();
+            Ok(Arc::new(values))
+        }
+        ("uniform", false) => {
+            let distr_float =
+                Uniform::<f64>::try_from(measure_min..=measure_max).map_err(|e| UniformError(e))?;
+            let values = (0..size)
+                .map(|_| distr_float.sample(rng))
+                .collect::<Float64Array>();
+            Ok(Arc::new(values))
+        }
+        ("normal", _) => {
+            let distr_normal = Normal::new(
+                (measure_min + measure_max) / 2.0,
+                (measure_max - measure_min) / 6.0,
+            )
+            .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
+            let values =
+                (0..size).map(|_| distr_normal.sample(rng).clamp(measure_min, measure_max));
+            if int_measure {
+                let values = values
+                    .map(|value| value.round().clamp(int_min, int_max) as i64)
+                    .collect::<Int64Array>();
+                Ok(Arc::new(values))
+            } else {
+                Ok(Arc::new(values.collect::<Float64Array>()))
+            }
+        }
+        _ => Err(PyValueError::new_err(format!(
+            "measure_dist should be one of \"uniform\", \"normal\", but got {}",
+            measure_dist
+        ))),
+    }
+}
+
+/// Join generators rely on dense keys, so NULLs in keys are not allowed.
+fn validate_keys(name: &str, keys: &Int64Array) -> PyResult<()> {
+    if keys.null_count() > 0 {
+        return Err(PyValueError::new_err(format!(
+            "{} should not contain NULLs, but got {} NULLs",
+            name,
+            keys.null_count()
+        )));
+    }
+    Ok(())
+}
+
+/// The size of the key space of join keys for nn distinct keys, like in the python side.
+fn join_key_levels(nn: i64) -> i64 {
+    (nn as f64 * 1.1) as i64
+}
+
+/// Shift keys by levels, so rhs keys from [levels + 1, 2 * levels] never match lhs keys.
+fn disjoint_keys(keys: Int64Array, levels: i64) -> Int64Array {
+    keys.unary(|key| key + levels)
+}
+
+/// Parse a filter predicate "<column> <op> <value>" of generate_groupby into its parts.
+fn parse_filter(filter: &str) -> PyResult<(String, String, f64)> {
+    let tokens = filter.split_whitespace().collect::<Vec<&str>>();
+    match tokens.as_slice() {
+        [column, op, value]
+            if ["id4", "id5", "id6", "v1", "v2", "v3"].contains(column)
+                && ["=", "!=", "<", "<=", ">", ">="].contains(op) =>
+        {
+            match value.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok((column.to_string(), op.to_string(), value)),
+                _ => Err(PyValueError::new_err(format!(
+                    "filter value should be a finite number, but got {}",
+                    value
+                ))),
+            }
+        }
+        _ => Err(PyValueError::new_err(format!(
+            "filter should be \"<column> <op> <value>\" with column from id4, id5, id6, v1, v2, v3 and op from =, !=, <, <=, >, >=, but got {}",
+            filter
+        ))),
+    }
+}
+
+/// Sort all the columns of the batch by given key columns (ascending, NULLs last).
+fn sort_by_keys(
+    batch: &RecordBatch,
+    keys: &[&str],
+) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let sort_columns = keys
+        .iter()
+        .map(|key| {
+            Ok(SortColumn {
+                values: batch.column(batch.schema().index_of(key)?).clone(),
+                options: Some(SortOptions {
+                    descending: false,
+                    nulls_first: false,
+                }),
+            })
+        })
+        .collect::<Result<Vec<SortColumn>, arrow::error::ArrowError>>()?;
+    let indices = lexsort_to_indices(&sort_columns, None)?;
+    take_record_batch(batch, &indices)
+}
+
+/// A size of the serialized ChaCha8Rng: seed (32), stream (8) and word position (16) bytes.
+const RNG_STATE_BYTES: usize = 56;
+
+/// Serialize the state of the rng as little-endian seed, stream and word position.
+fn rng_to_state(rng: &ChaCha8Rng) -> Vec<u8> {
+    let mut state = Vec::with_capacity(RNG_STATE_BYTES);
+    state.extend_from_slice(&rng.get_seed());
+    state.extend_from_slice(&rng.get_stream().to_le_bytes());
+    state.extend_from_slice(&rng.get_word_pos().to_le_bytes());
+    state
+}
+
+/// Restore the rng from the state serialized by rng_to_state.
+fn rng_from_state(state: &[u8]) -> PyResult<ChaCha8Rng> {
+    if state.len() != RNG_STATE_BYTES {
+        return Err(PyValueError::new_err(format!(
+            "rng state should be {} bytes, but got {}",
+            RNG_STATE_BYTES,
+            state.len()
+        )));
+    }
+    let mut rng = ChaCha8Rng::from_seed(state[0..32].try_into().unwrap());
+    rng.set_stream(u64::from_le_bytes(state[32..40].try_into().unwrap()));
+    rng.set_word_pos(u128::from_le_bytes(state[40..56].try_into().unwrap()));
+    Ok(rng)
+}
+
+/// Base columns of the group-by dataset in the order of generation.
+const GROUPBY_COLUMNS: [&str; 9] = ["id1", "id2", "id3", "id4", "id5", "id6", "v1", "v2", "v3"];
+
+/// An amount of leading rows that are generated and sliced off with with_offset.
+/// It is not a multiple of 8, so validity bitmaps are not byte-aligned either.
+const WITH_OFFSET_PAD_ROWS: i64 = 3;
+
+/// Precision and scale of v3 emitted as Decimal256.
+const V3_DECIMAL_PRECISION: u8 = 40;
+const V3_DECIMAL_SCALE: i8 = 4;
+
+/// Values that replace corrupted cells, out of the range of generated values.
+const CORRUPT_INT: i64 = -1_000_000_000;
+const CORRUPT_FLOAT: f64 = -1000.0;
+
+/// Pathological floats injected into v3 with v3_edge_rate.
+const V3_EDGE_VALUES: [f64; 4] = [-0.0, f64::MIN_POSITIVE, 5e-324, 1e308];
+
+/// An upper bound of the size of a single blob, 1 MiB.
+const MAX_BLOB_BYTES: usize = 1 << 20;
+
+/// An upper bound of the length of the feature vector.
+const MAX_FEATURE_DIM: usize = 1024;
+
+/// Parameters of generate_groupby, see its docstring for the meaning of each of them.
+/// Defaults are the defaults of the Python signature, except n, k, nas, seed and batch_size
+/// that have no defaults and are 0, so they should be always set.
+#[derive(Clone)]
+struct GroupByParams<'a> {
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    v3_round: Option<i32>,
+    with_map: bool,
+    nas_string: Option<i64>,
+    nas_int: Option<i64>,
+    shuffle: bool,
+    with_duration: bool,
+    dur_min: i64,
+    dur_max: i64,
+    key_stride: i64,
+    v3_nan_rate: f64,
+    monotonic_ts: bool,
+    ts_start: i64,
+    hex_ids: bool,
+    unique_id3: bool,
+    id4_id5_corr: f64,
+    blob_bytes: usize,
+    rng_state: Option<&'a [u8]>,
+    feature_dim: usize,
+    fast_nulls: bool,
+    id2_values: Option<Vec<i64>>,
+    force_nullable: bool,
+    key_dist: &'a str,
+    key_dist_shape: f64,
+    nk_round: &'a str,
+    with_time: bool,
+    column_seeds: Option<HashMap<String, u64>>,
+    strict_width: bool,
+    float16: bool,
+    null_sentinel: Option<String>,
+    null_bias_groups: Option<Vec<i64>>,
+    null_bias_nas: i64,
+    id1_offset: i64,
+    corrupt_rate: f64,
+    corrupt_target: Option<&'a str>,
+    cumulative_v1: bool,
+    exact_percent: bool,
+    v3_edge_rate: f64,
+    sort_by: Option<Vec<String>>,
+    key_seed: Option<u64>,
+    value_seed: Option<u64>,
+    binary_id3: bool,
+    run_length: i64,
+    v3_collision_levels: Option<i64>,
+    seasonal: bool,
+    column_order: Option<Vec<String>>,
+    max_memory_bytes: Option<i64>,
+    index_hashed_keys: bool,
+    decimal256: bool,
+    with_offset: bool,
+    filter: Option<&'a str>,
+    with_checksum: bool,
+    target_compressibility: f64,
+    field_metadata: Option<HashMap<String, HashMap<String, String>>>,
+    messy_strings: bool,
+    messy_rate: f64,
+    group_sizes: Option<Vec<i64>>,
+    with_string_list: bool,
+}
+
+impl Default for GroupByParams<'_> {
+    fn default() -> Self {
+        GroupByParams {
+            n: 0,
+            k: 0,
+            nas: 0,
+            seed: 0,
+            batch_size: 0,
+            v3_round: None,
+            with_map: false,
+            nas_string: None,
+            nas_int: None,
+            shuffle: false,
+            with_duration: false,
+            dur_min: 0,
+            dur_max: 86_400,
+            key_stride: 1,
+            v3_nan_rate: 0.0,
+            monotonic_ts: false,
+            ts_start: 0,
+            hex_ids: false,
+            unique_id3: false,
+            id4_id5_corr: 0.0,
+            blob_bytes: 0,
+            rng_state: None,
+            feature_dim: 0,
+            fast_nulls: false,
+            id2_values: None,
+            force_nullable: false,
+            key_dist: "uniform",
+            key_dist_shape: 1.0,
+            nk_round: "floor",
+            with_time: false,
+            column_seeds: None,
+            strict_width: false,
+            float16: false,
+            null_sentinel: None,
+            null_bias_groups: None,
+            null_bias_nas: 50,
+            id1_offset: 0,
+            corrupt_rate: 0.0,
+            corrupt_target: None,
+            cumulative_v1: false,
+            exact_percent: true,
+            v3_edge_rate: 0.0,
+            sort_by: None,
+            key_seed: None,
+            value_seed: None,
+            binary_id3: false,
+            run_length: 1,
+            v3_collision_levels: None,
+            seasonal: false,
+            column_order: None,
+            max_memory_bytes: None,
+            index_hashed_keys: false,
+            decimal256: false,
+            with_offset: false,
+            filter: None,
+            with_checksum: false,
+            target_compressibility: 0.0,
+            field_metadata: None,
+            messy_strings: false,
+            messy_rate: 0.1,
+            group_sizes: None,
+            with_string_list: false,
+        }
+    }
+}
+
+/**
+Generate H2O group-by dataset.
+Running this function multiple time with the same seed
+will constantly return exactly the same batch!
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+    Passing a negative value or zero may tend to runtime errors / panic.
+:param k: int
+    An amount of grouping keys. Should be positive.
+    Passing a negative value or zero may tend to runtime errors / panic.
+:param nas: int
+    A number from 1 to 100 that represent a percent of NULLs, see exact_percent.
+    Passing a value not from [0-100] may tend to unpredictable behavior.
+:param seed: int
+    A random seed value. Should be positive!
+    Passing a negative value may tend to unpredictable behavior.
+:param batch_size: int
+    A size of the output batch. If batch_size is greater than n,
+    only n rows are generated, so the output never exceeds n rows.
+:param v3_round: int | None
+    If passed, v3 values are rounded to the given number of decimal places.
+    By default values are not rounded.
+:param with_map: bool
+    If true, an additional column m1 of type Map<Utf8, Int64> is appended.
+    Each row holds two entries with keys "a" (1-5) and "b" (1-15).
+:param nas_string: int | None
+    If passed, overrides nas for string columns id1, id2 and id3.
+    Should be from [0-100]. With 100 and exact_percent these columns are entirely NULL
+    and generated in bulk without random draws.
+:param nas_int: int | None
+    If passed, overrides nas for integer columns id4, id5 and id6.
+    Should be from [0-100].
+:param shuffle: bool
+    If true, rows of the batch are permuted by a seeded permutation.
+    The content of the batch is the same as with shuffle=False.
+:param with_duration: bool
+    If true, an additional column dur of type Duration(Second) is appended.
+    Values are from [dur_min, dur_max], NULLs are generated with respect to nas.
+:param dur_min: int
+    A minimal duration in seconds, 0 by default.
+:param dur_max: int
+    A maximal duration in seconds, 86400 (one day) by default.
+    Should be greater or equal than dur_min.
+:param key_stride: int
+    A multiplier for integer keys id4, id5 and id6, 1 by default.
+    For example, key_stride=1000 gives keys 1000, 2000, ... with the same cardinality.
+    Should be positive and max(k, N/K) * key_stride should fit into int64.
+:param v3_nan_rate: float
+    A fraction of v3 values from [0, 1] that are replaced by NaN, 0.0 by default.
+:param monotonic_ts: bool
+    If true, an additional column ts of type Timestamp(Microsecond) is appended.
+    Values are strictly increasing, starting from ts_start with a random step
+    from 1 microsecond to 1 second per row.
+:param ts_start: int
+    The first value of ts in microseconds since the epoch, 0 by default.
+    To continue a stream pass the last value of the previous batch plus one.
+:param hex_ids: bool
+    If true, numeric parts of id1, id2 and id3 are formatted as lowercase hex.
+    The width of id3 is 9 instead of 10 in that case.
+:param unique_id3: bool
+    If true, id3 values are sampled from 1-N/K without replacement,
+    so all the values of id3 in the batch are unique.
+    batch_size should be less or equal than N/K in that case.
+    Not compatible with target_compressibility and corrupt_target="id3", which repeat id3 values.
+:param id4_id5_corr: float
+    A probability from [0, 1] that id5 is equal to id4 instead of being sampled
+    independently, 0.0 by default.
+:param blob_bytes: int
+    If positive, an additional column blob of type LargeBinary is appended.
+    Each row holds blob_bytes of pseudo-random bytes, NULLs are generated with respect to nas.
+    Should be not greater than 1 MiB, 0 (no column) by default.
+:param rng_state: bytes | None
+    If passed, the random generator is restored from this state instead of the seed.
+    See groupby_rng_state for the format.
+:param feature_dim: int
+    If positive, an additional column feat of type FixedSizeList(Float64, feature_dim)
+    is appended. Each row holds feature_dim independent uniform values from [0, 1).
+    Should be not greater than 1024, 0 (no column) by default.
+:param fast_nulls: bool
+    If true, values of id columns are generated without NULLs first and validity
+    buffers are sampled afterwards in one pass per column. It is faster but gives
+    a different batch than the default path for the same seed.
+:param id2_values: list[int] | None
+    If passed, id2 is sampled uniformly from these values instead of 1-N/K.
+    Should be non-empty.
+:param force_nullable: bool
+    If true, all the fields of the schema (including v1, v2 and v3) are declared
+    as nullable, even if there are no NULLs in the data.
+:param key_dist: str
+    A distribution of integer keys id4, id5 and id6: "uniform" (default) or "pareto".
+    Pareto values are truncated to integers and clamped into the range of the key.
+:param key_dist_shape: float
+    A shape of the pareto distribution, smaller values give heavier tails.
+    Should be positive, 1.0 by default.
+:param nk_round: str
+    How N/K, the upper bound of id2, id3 and id6, is computed for non-divisible n and k:
+    "floor" (default), "ceil" or "round". For example, n=150 and k=100 give
+    N/K=1 with "floor", so id2, id3 and id6 have a single value.
+:param with_time: bool
+    If true, an additional column t1 of type Time64(Microsecond) is appended.
+    Values are uniform across a day, NULLs are generated with respect to nas.
+:param column_seeds: dict[str, int] | None
+    If passed, columns from id1, ..., id6, v1, v2, v3 named in keys are generated from
+    their own random streams seeded by values. The main stream still makes the draws
+    of these columns, so overriding the seed of one column does not change other columns,
+    except NULLs of id2, ..., id6 with null_bias_groups (id1) and id5 with id4_id5_corr (id4).
+:param strict_width: bool
+    The numeric part of id1 is padded to 3 digits and of id3 to 10 digits (9 for hex).
+    If k or N/K do not fit these widths, the padding is widened to fit the maximal value.
+    If strict_width is true, an error is raised instead.
+:param float16: bool
+    If true, v3 is emitted as Float16 instead of Float64. Half precision keeps
+    about 3 significant decimal digits, so values from [0, 100] are rounded
+    to a step of 1/16 at most.
+:param null_sentinel: str | None
+    If passed, cells of id1, ..., id6 selected as NULL with respect to nas are filled
+    with the sentinel instead: as is for string columns and parsed as an integer
+    for integer columns, so the sentinel should be a valid integer.
+:param null_bias_groups: list[int] | None
+    Groups (numbers of id1 from 1 to k, before id1_offset) where NULLs are concentrated.
+    In rows with id1 from these groups, id2, ..., id6 are NULL with the rate null_bias_nas
+    instead of nas_string and nas_int. Not supported with fast_nulls.
+:param null_bias_nas: int
+    A number from 0 to 100, the percent of NULLs in rows from null_bias_groups.
+:param id1_offset: int
+    An offset of id1 numbers, so id1 is from [1 + id1_offset, k + id1_offset].
+    It allows to combine outputs with disjoint key spaces. Should be non-negative.
+    The padding of id1 is widened to fit the maximal value.
+:param corrupt_rate: float
+    A fraction from [0, 1] of rows where the value of corrupt_target is replaced
+    by an out-of-range one: an empty string for id1, id2 and id3, -1000000000
+    for id4, id5, id6, v1 and v2 and -1000.0 for v3. Exactly round(corrupt_rate * rows)
+    rows are chosen with the seeded generator, so the output is reproducible.
+:param corrupt_target: str | None
+    A base column from id1, ..., id6, v1, v2, v3 to corrupt with corrupt_rate.
+:param cumulative_v1: bool
+    If true, rows are sorted by id1 (NULLs last) and v1 is replaced by the running sum
+    of sampled values within each id1 group, so v1 is non-decreasing in each group.
+    Not compatible with shuffle.
+:param exact_percent: bool
+    If true (default), nas is an exact percent: the NULL check draws from [0, 100),
+    so nas=50 gives 50% of NULLs and nas=100 gives only NULLs. If false, the legacy
+    check draws from [0, 100] and gives nas/101 of NULLs, see expected_null_fraction.
+    Migration note: outputs with 0 < nas generated before exact_percent was added
+    are reproduced with exact_percent=False; outputs with nas=0 do not change.
+:param v3_edge_rate: float
+    A probability from [0, 1] that a v3 value is replaced by an edge case float:
+    -0.0, the smallest positive normal, the smallest subnormal or 1e308.
+    Edge values are chosen with the seeded generator and are not rounded by v3_round.
+:param sort_by: list[str] | None
+    If passed, rows are sorted by the listed columns in order (ascending, NULLs last).
+    Columns should exist in the output. Not compatible with shuffle.
+:param key_seed: int | None
+    If passed, id1, ..., id6 are generated from their own random stream seeded by it.
+    Otherwise they derive from seed through the main stream.
+:param value_seed: int | None
+    If passed, v1, v2 and v3 are generated from their own random stream seeded by it.
+    Otherwise they derive from seed through the main stream. The main stream makes
+    the same draws whether these seeds are passed or not, so passing or changing value_seed
+    alone leaves id columns byte-identical and key_seed alone leaves value columns
+    byte-identical. Seeds from column_seeds take precedence over both.
+:param binary_id3: bool
+    If true, id3 is emitted as Binary holding the UTF-8 bytes of the idNNN value
+    instead of Utf8. NULLs are kept.
+:param run_length: int
+    Each sampled id4 value (or NULL) is repeated in run_length consecutive rows
+    before the next one is sampled, so id4 has compressible runs. Should be positive,
+    1 by default. Runs restart at batch boundaries.
+:param v3_collision_levels: int | None
+    If passed, v3 is quantized to the centers of that many equal bins of [0, 100],
+    so v3 has at most v3_collision_levels distinct values and many exact-equal floats.
+    Should be positive. NaN and edge values from v3_nan_rate and v3_edge_rate are kept.
+:param seasonal: bool
+    If true, v3 is multiplied by 1 + 0.5 * sin(2 * pi * dow / 7), where dow is the day
+    of week of ts (0 is Monday), so weekend rows have systematically lower values.
+    NaN and edge values are kept, v3_round is applied after. Requires monotonic_ts.
+    Not compatible with v3_collision_levels.
+:param column_order: list[str] | None
+    If passed, columns of the output are reordered to match it. Should be a permutation
+    of the names of all the output columns, including optional ones.
+:param max_memory_bytes: int | None
+    If passed and the batch is estimated to be larger, it is generated in sub-batches
+    of at most this estimated size that are concatenated, which bounds the memory of
+    builders. Sub-batches use seeds derived from the seed, so the output differs from
+    the one without the budget; shuffle and filter are applied within each sub-batch.
+    Should be positive. Not compatible with rng_state, unique_id3, cumulative_v1, sort_by,
+    with_offset and group_sizes.
+:param index_hashed_keys: bool
+    If true, id1, ..., id6 of each row are generated from a random stream seeded by a hash
+    of the seed, the index of the row in the output and the index of the column instead
+    of the sequential stream, so keys of a row do not depend on preceding rows and
+    batch_size. Takes precedence over column_seeds and key_seed for id columns.
+:param decimal256: bool
+    If true, v3 is emitted as Decimal256(40, 4), rounded to 4 decimal places.
+    Not compatible with float16, v3_nan_rate and v3_edge_rate, because NaN
+    and edge values are not representable.
+:param with_offset: bool
+    If true, 3 extra leading rows are generated and sliced off,
+    so all the arrays of the output have a non-zero offset into their buffers.
+    The output has the same amount of rows, but values differ from the default ones.
+    Padding rows are sliced off before cumulative_v1, sort_by, shuffle and filter,
+    which copy the arrays, so offsets are zero when any of them is used.
+    Not compatible with max_memory_bytes.
+:param filter: str | None
+    If passed, only rows matching the predicate are returned, so the output may have
+    less rows than batch_size. The predicate is "<column> <op> <value>" separated
+    by spaces, where column is one of id4, id5, id6, v1, v2, v3, op is one of
+    =, !=, <, <=, >, >= and value is a number, for example "v3 > 50". NULLs never match.
+:param with_checksum: bool
+    If true, the row_hash column of type Int64 is appended with the FNV-1a 64-bit hash
+    of id1, ..., id6, v1, v2, v3 of the row, reinterpreted as signed. Each value is
+    encoded as the byte 0 if NULL or as the byte 1 followed by the value: the u32 LE
+    length and bytes for strings and binaries, i64 LE for integers and f64 LE for floats
+    (other float types are cast to f64).
+:param target_compressibility: float
+    A probability from [0, 1] that a row repeats id1, ..., id6, v1, v2, v3 of the previous
+    row instead of its own random values: 0 (default) gives random data, values close to 1
+    give highly repetitive data that compresses better. Optional columns are not repeated.
+:param field_metadata: dict[str, dict[str, str]] | None
+    If passed, the metadata is attached to the named fields of the output schema,
+    for example {"v3": {"unit": "usd"}}. Columns should exist in the output.
+:param messy_strings: bool
+    If true, a messy_rate fraction of non-NULL id1 values is made messy to exercise
+    trim and normalization: padded with leading, trailing or both spaces, or upper-cased.
+:param messy_rate: float
+    A probability from [0, 1] that an id1 value is messy with messy_strings, 0.1 by default.
+:param group_sizes: list[int] | None
+    If passed, exactly group_sizes[i - 1] rows of the batch have the id1 group i, in a shuffled
+    order. It should have k non-negative elements summing to min(n, batch_size).
+    id1 has no NULLs in that case. Not compatible with with_offset, fast_nulls,
+    nas_string=100, max_memory_bytes, target_compressibility, messy_strings,
+    corrupt_target="id1" and filter, which change the sizes of groups.
+:param with_string_list: bool
+    If true, the tags column of type List(Utf8) is appended, where each row holds
+    from 0 to 3 tags formatted as id{:03} from 1 to k. A percent nas of lists is NULL,
+    which is distinct from empty lists.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    n,
+    k,
+    nas,
+    seed,
+    batch_size,
+    v3_round=None,
+    with_map=false,
+    nas_string=None,
+    nas_int=None,
+    shuffle=false,
+    with_duration=false,
+    dur_min=0,
+    dur_max=86_400,
+    key_stride=1,
+    v3_nan_rate=0.0,
+    monotonic_ts=false,
+    ts_start=0,
+    hex_ids=false,
+    unique_id3=false,
+    id4_id5_corr=0.0,
+    blob_bytes=0,
+    rng_state=None,
+    feature_dim=0,
+    fast_nulls=false,
+    id2_values=None,
+    force_nullable=false,
+    key_dist="uniform",
+    key_dist_shape=1.0,
+    nk_round="floor",
+    with_time=false,
+    column_seeds=None,
+    strict_width=false,
+    float16=false,
+    null_sentinel=None,
+    null_bias_groups=None,
+    null_bias_nas=50,
+    id1_offset=0,
+    corrupt_rate=0.0,
+    corrupt_target=None,
+    cumulative_v1=false,
+    exact_percent=true,
+    v3_edge_rate=0.0,
+    sort_by=None,
+    key_seed=None,
+    value_seed=None,
+    binary_id3=false,
+    run_length=1,
+    v3_collision_levels=None,
+    seasonal=false,
+    column_order=None,
+    max_memory_bytes=None,
+    index_hashed_keys=false,
+    decimal256=false,
+    with_offset=false,
+    filter=None,
+    with_checksum=false,
+    target_compressibility=0.0,
+    field_metadata=None,
+    messy_strings=false,
+    messy_rate=0.1,
+    group_sizes=None,
+    with_string_list=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    v3_round: Option<i32>,
+    with_map: bool,
+    nas_string: Option<i64>,
+    nas_int: Option<i64>,
+    shuffle: bool,
+    with_duration: bool,
+    dur_min: i64,
+    dur_max: i64,
+    key_stride: i64,
+    v3_nan_rate: f64,
+    monotonic_ts: bool,
+    ts_start: i64,
+    hex_ids: bool,
+    unique_id3: bool,
+    id4_id5_corr: f64,
+    blob_bytes: usize,
+    rng_state: Option<&[u8]>,
+    feature_dim: usize,
+    fast_nulls: bool,
+    id2_values: Option<Vec<i64>>,
+    force_nullable: bool,
+    key_dist: &str,
+    key_dist_shape: f64,
+    nk_round: &str,
+    with_time: bool,
+    column_seeds: Option<HashMap<String, u64>>,
+    strict_width: bool,
+    float16: bool,
+    null_sentinel: Option<String>,
+    null_bias_groups: Option<Vec<i64>>,
+    null_bias_nas: i64,
+    id1_offset: i64,
+    corrupt_rate: f64,
+    corrupt_target: Option<&str>,
+    cumulative_v1: bool,
+    exact_percent: bool,
+    v3_edge_rate: f64,
+    sort_by: Option<Vec<String>>,
+    key_seed: Option<u64>,
+    value_seed: Option<u64>,
+    binary_id3: bool,
+    run_length: i64,
+    v3_collision_levels: Option<i64>,
+    seasonal: bool,
+    column_order: Option<Vec<String>>,
+    max_memory_bytes: Option<i64>,
+    index_hashed_keys: bool,
+    decimal256: bool,
+    with_offset: bool,
+    filter: Option<&str>,
+    with_checksum: bool,
+    target_compressibility: f64,
+    field_metadata: Option<HashMap<String, HashMap<String, String>>>,
+    messy_strings: bool,
+    messy_rate: f64,
+    group_sizes: Option<Vec<i64>>,
+    with_string_list: bool,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    groupby_with_params(GroupByParams {
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        v3_round,
+        with_map,
+        nas_string,
+        nas_int,
+        shuffle,
+        with_duration,
+        dur_min,
+        dur_max,
+        key_stride,
+        v3_nan_rate,
+        monotonic_ts,
+        ts_start,
+        hex_ids,
+        unique_id3,
+        id4_id5_corr,
+        blob_bytes,
+        rng_state,
+        feature_dim,
+        fast_nulls,
+        id2_values,
+        force_nullable,
+        key_dist,
+        key_dist_shape,
+        nk_round,
+        with_time,
+        column_seeds,
+        strict_width,
+        float16,
+        null_sentinel,
+        null_bias_groups,
+        null_bias_nas,
+        id1_offset,
+        corrupt_rate,
+        corrupt_target,
+        cumulative_v1,
+        exact_percent,
+        v3_edge_rate,
+        sort_by,
+        key_seed,
+        value_seed,
+        binary_id3,
+        run_length,
+        v3_collision_levels,
+        seasonal,
+        column_order,
+        max_memory_bytes,
+        index_hashed_keys,
+        decimal256,
+        with_offset,
+        filter,
+        with_checksum,
+        target_compressibility,
+        field_metadata,
+        messy_strings,
+        messy_rate,
+        group_sizes,
+        with_string_list,
+    })
+    .map(PyArrowType)
+}
+
+/// Generate a group-by batch, see generate_groupby.
+fn groupby_with_params(params: GroupByParams) -> PyResult<RecordBatch> {
+    if let Some(max_memory_bytes) = params.max_memory_bytes {
+        if max_memory_bytes <= 0 {
+            return Err(PyValueError::new_err(format!(
+                "max_memory_bytes should be positive, but got {}",
+                max_memory_bytes
+            )));
+        }
+        if params.rng_state.is_some()
+            || params.unique_id3
+            || params.cumulative_v1
+            || params.sort_by.is_some()
+            || params.with_offset
+            || params.group_sizes.is_some()
+        {
+            return Err(PyValueError::new_err(
+                "max_memory_bytes is not compatible with rng_state, unique_id3, cumulative_v1, sort_by, with_offset and group_sizes",
+            ));
+        }
+        let rows = params.batch_size.min(params.n);
+        let generate = |seed: i64, batch_size: i64, ts_start: i64, filter: Option<&str>| {
+            groupby_with_params(GroupByParams {
+                seed,
+                batch_size,
+                ts_start,
+                filter,
+                max_memory_bytes: None,
+                ..params.clone()
+            })
+        };
+        // The probe is not filtered, so it is never empty
+        let probe = generate(
+            params.seed,
+            rows.min(BYTE_BUDGET_PROBE_ROWS),
+            params.ts_start,
+            None,
+        )?;
+        let row_bytes =
+            estimated_batch_bytes(&probe).map_err(ArrowError)? as f64 / probe.num_rows() as f64;
+        let sub_rows = ((max_memory_bytes as f64 / row_bytes) as i64).clamp(1, rows);
+        if sub_rows < rows {
+            let mut batches = Vec::new();
+            let mut ts_start = params.ts_start;
+            for (index, start) in (0..rows).step_by(sub_rows as usize).enumerate() {
+                let batch = generate(
+                    derive_seed(params.seed, index as u64),
+                    sub_rows.min(rows - start),
+                    ts_start,
+                    params.filter,
+                )?;
+                if let Some(ts) = batch.column_by_name("ts") {
+                    // Carry the last timestamp to keep ts strictly increasing across sub-batches
+                    let ts = ts.as_primitive::<TimestampMicrosecondType>();
+                    ts_start = ts.values().iter().max().map_or(ts_start, |ts| ts + 1);
+                }
+                batches.push(batch);
+            }
+            let batch = concat_batches(&batches[0].schema(), &batches).map_err(ArrowError)?;
+            return Ok(batch);
+        }
+    }
+
+    let mut rng = match params.rng_state {
+        Some(state) => rng_from_state(state)?,
+        None => ChaCha8Rng::seed_from_u64(params.seed as u64),
+    };
+    groupby_with_rng(params, &mut rng)
+}
+
+/// Generate a group-by batch from the main random stream rng, see generate_groupby.
+/// The word position of rng after the call tells how much of the stream the batch consumed.
+fn groupby_with_rng(params: GroupByParams, rng: &mut ChaCha8Rng) -> PyResult<RecordBatch> {
+    let GroupByParams {
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        v3_round,
+        with_map,
+        nas_string,
+        nas_int,
+        shuffle,
+        with_duration,
+        dur_min,
+        dur_max,
+        key_stride,
+        v3_nan_rate,
+        monotonic_ts,
+        ts_start,
+        hex_ids,
+        unique_id3,
+        id4_id5_corr,
+        blob_bytes,
+        rng_state: _,
+        feature_dim,
+        fast_nulls,
+        id2_values,
+        force_nullable,
+        key_dist,
+        key_dist_shape,
+        nk_round,
+        with_time,
+        column_seeds,
+        strict_width,
+        float16,
+        null_sentinel,
+        null_bias_groups,
+        null_bias_nas,
+        id1_offset,
+        corrupt_rate,
+        corrupt_target,
+        cumulative_v1,
+        exact_percent,
+        v3_edge_rate,
+        sort_by,
+        key_seed,
+        value_seed,
+        binary_id3,
+        run_length,
+        v3_collision_levels,
+        seasonal,
+        column_order,
+        max_memory_bytes: _,
+        index_hashed_keys,
+        decimal256,
+        with_offset,
+        filter,
+        with_checksum,
+        target_compressibility,
+        field_metadata,
+        messy_strings,
+        messy_rate,
+        group_sizes,
+        with_string_list,
+    } = params;
+
+    let nas_string = nas_string.unwrap_or(nas);
+    let nas_int = nas_int.unwrap_or(nas);
+    for (name, value) in [("nas_string", nas_string), ("nas_int", nas_int)] {
+        if !(0..=100).contains(&value) {
+            return Err(PyValueError::new_err(format!(
+                "{} should be in [0, 100], but got {}",
+                name, value
+            )));
+        }
+    }
+
+    let distr_k = Uniform::<i64>::try_from(1..=k).map_err(|e| UniformError(e))?;
+    let nk = match nk_round {
+        "floor" => n / k,
+        "ceil" => n / k + (n % k != 0) as i64,
+        "round" => n / k + (2 * (n % k) >= k) as i64,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "nk_round should be one of \"floor\", \"ceil\", \"round\", but got {}",
+                nk_round
+            )))
+        }
+    };
+    let distr_nk = Uniform::<i64>::try_from(1..=nk).map_err(|e| UniformError(e))?;
+    let distr_5 = Uniform::<i64>::try_from(1..=5).map_err(|e| UniformError(e))?;
+    let distr_15 = Uniform::<i64>::try_from(1..=15).map_err(|e| UniformError(e))?;
+    let distr_float = Uniform::<f64>::try_from(0.0..=100.0).map_err(|e| UniformError(e))?;
+    // The value is kept if the draw is >= nas, so [0, 100) gives exactly nas% of NULLs
+    let distr_nas = if exact_percent {
+        Uniform::<i64>::try_from(0..100).map_err(|e| UniformError(e))?
+    } else {
+        Uniform::<i64>::try_from(0..=100).map_err(|e| UniformError(e))?
+    };
+    if dur_min > dur_max {
+        return Err(PyValueError::new_err(format!(
+            "dur_min should be less or equal than dur_max, but got {} > {}",
+            dur_min, dur_max
+        )));
+    }
+    if key_stride <= 0 {
+        return Err(PyValueError::new_err(format!(
+            "key_stride should be positive, but got {}",
+            key_stride
+        )));
+    }
+    // id4 and id5 are up to k and id6 is up to N/K before the stride is applied
+    if k.max(nk).checked_mul(key_stride).is_none() {
+        return Err(PyValueError::new_err(format!(
+            "max(k, n/k) * key_stride should fit into int64, but got {} * {}",
+            k.max(nk),
+            key_stride
+        )));
+    }
+    if !(0.0..=1.0).contains(&v3_nan_rate) {
+        return Err(PyValueError::new_err(format!(
+            "v3_nan_rate should be in [0, 1], but got {}",
+            v3_nan_rate
+        )));
+    }
+    if blob_bytes > MAX_BLOB_BYTES {
+        return Err(PyValueError::new_err(format!(
+            "blob_bytes should be not greater than {}, but got {}",
+            MAX_BLOB_BYTES, blob_bytes
+        )));
+    }
+    if feature_dim > MAX_FEATURE_DIM {
+        return Err(PyValueError::new_err(format!(
+            "feature_dim should be not greater than {}, but got {}",
+            MAX_FEATURE_DIM, feature_dim
+        )));
+    }
+    if !(0.0..=1.0).contains(&id4_id5_corr) {
+        return Err(PyValueError::new_err(format!(
+            "id4_id5_corr should be in [0, 1], but got {}",
+            id4_id5_corr
+        )));
+    }
+    let int_sentinel = match &null_sentinel {
+        Some(sentinel) => Some(sentinel.parse::<i64>().map_err(|e| {
+            PyValueError::new_err(format!(
+                "null_sentinel should be a valid integer, but got {}: {}",
+                sentinel, e
+            ))
+        })?),
+        None => None,
+    };
+    if !(0.0..=1.0).contains(&v3_edge_rate) {
+        return Err(PyValueError::new_err(format!(
+            "v3_edge_rate should be in [0, 1], but got {}",
+            v3_edge_rate
+        )));
+    }
+    if !(0.0..=1.0).contains(&corrupt_rate) {
+        return Err(PyValueError::new_err(format!(
+            "corrupt_rate should be in [0, 1], but got {}",
+            corrupt_rate
+        )));
+    }
+    let corrupt_index = match corrupt_target {
+        Some(target) => Some(
+            GROUPBY_COLUMNS
+                .iter()
+                .position(|name| *name == target)
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "corrupt_target should be one of {:?}, but got {}",
+                        GROUPBY_COLUMNS, target
+                    ))
+                })?,
+        ),
+        None if corrupt_rate > 0.0 => {
+            return Err(PyValueError::new_err(
+                "corrupt_target should be passed if corrupt_rate is positive",
+            ));
+        }
+        None => None,
+    };
+    if sort_by.is_some() && shuffle {
+        return Err(PyValueError::new_err(
+            "sort_by is not compatible with shuffle",
+        ));
+    }
+    if cumulative_v1 && shuffle {
+        return Err(PyValueError::new_err(
+            "cumulative_v1 requires rows grouped by id1 and is not compatible with shuffle",
+        ));
+    }
+    let null_bias_groups = null_bias_groups.unwrap_or_default();
+    if let Some(group) = null_bias_groups
+        .iter()
+        .find(|group| !(1..=k).contains(*group))
+    {
+        return Err(PyValueError::new_err(format!(
+            "null_bias_groups should be in [1, {}], but got {}",
+            k, group
+        )));
+    }
+    if !(0..=100).contains(&null_bias_nas) {
+        return Err(PyValueError::new_err(format!(
+            "null_bias_nas should be in [0, 100], but got {}",
+            null_bias_nas
+        )));
+    }
+    if fast_nulls && !null_bias_groups.is_empty() {
+        return Err(PyValueError::new_err(
+            "null_bias_groups is not supported with fast_nulls",
+        ));
+    }
+    let null_bias_groups = null_bias_groups.into_iter().collect::<HashSet<i64>>();
+    let distr_dur = Uniform::<i64>::try_from(dur_min..=dur_max).map_err(|e| UniformError(e))?;
+    let distr_id2_values = match &id2_values {
+        Some(values) if values.is_empty() => {
+            return Err(PyValueError::new_err("id2_values should be non-empty"));
+        }
+        Some(values) => {
+            Some(Uniform::<usize>::try_from(0..values.len()).map_err(|e| UniformError(e))?)
+        }
+        None => None,
+    };
+    let pareto = match key_dist {
+        "uniform" => None,
+        "pareto" => Some(Pareto::new(1.0, key_dist_shape).map_err(|e| {
+            PyValueError::new_err(format!(
+                "key_dist_shape should be positive, but got {}: {}",
+                key_dist_shape, e
+            ))
+        })?),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "key_dist should be one of \"uniform\", \"pareto\", but got {}",
+                key_dist
+            )))
+        }
+    };
+    let distr_time = Uniform::<i64>::try_from(0..86_400_000_000).map_err(|e| UniformError(e))?;
+    let distr_ts_step = Uniform::<i64>::try_from(1..=1_000_000).map_err(|e| UniformError(e))?;
+    let v3_scale = v3_round.map(|digits| 10f64.powi(digits));
+    if !(0.0..=1.0).contains(&messy_rate) {
+        return Err(PyValueError::new_err(format!(
+            "messy_rate should be in [0, 1], but got {}",
+            messy_rate
+        )));
+    }
+    if !(0.0..=1.0).contains(&target_compressibility) {
+        return Err(PyValueError::new_err(format!(
+            "target_compressibility should be in [0, 1], but got {}",
+            target_compressibility
+        )));
+    }
+    if let Some(levels) = v3_collision_levels.filter(|levels| *levels <= 0) {
+        return Err(PyValueError::new_err(format!(
+            "v3_collision_levels should be positive, but got {}",
+            levels
+        )));
+    }
+    let v3_levels = v3_collision_levels.map(|levels| levels as f64);
+    let predicate = filter.map(parse_filter).transpose()?;
+    if seasonal && !monotonic_ts {
+        return Err(PyValueError::new_err("seasonal requires monotonic_ts"));
+    }
+    if seasonal && v3_collision_levels.is_some() {
+        // Scaling quantized values would multiply the amount of distinct values
+        return Err(PyValueError::new_err(
+            "seasonal is not compatible with v3_collision_levels",
+        ));
+    }
+    if decimal256 && (float16 || (v3_nan_rate > 0.0) || (v3_edge_rate > 0.0)) {
+        return Err(PyValueError::new_err(
+            "decimal256 is not compatible with float16, v3_nan_rate and v3_edge_rate",
+        ));
+    }
+
+    let batch_size = batch_size.min(n); // never generate more than n rows
+    let pad_rows = if with_offset { WITH_OFFSET_PAD_ROWS } else { 0 };
+    let batch_size = batch_size + pad_rows; // padding rows are sliced off at the end
+    let item_capacity = batch_size as usize; // validataion is on the python side
+
+    // Widths of numeric parts of id1 and id3, widened if the maximal value does not fit
+    let radix = if hex_ids { 16 } else { 10 };
+    if id1_offset < 0 {
+        return Err(PyValueError::new_err(format!(
+            "id1_offset should be non-negative, but got {}",
+            id1_offset
+        )));
+    }
+    if run_length < 1 {
+        return Err(PyValueError::new_err(format!(
+            "run_length should be positive, but got {}",
+            run_length
+        )));
+    }
+    let id1_max = k.checked_add(id1_offset).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "k + id1_offset should fit into int64, but got {} + {}",
+            k, id1_offset
+        ))
+    })?;
+    let id1_width = num_digits(id1_max, radix).max(3);
+    let id3_width = num_digits(nk, radix).max(if hex_ids { 9 } else { 10 });
+    if strict_width && ((id1_width > 3) || (id3_width > if hex_ids { 9 } else { 10 })) {
+        return Err(PyValueError::new_err(format!(
+            "k + id1_offset={} or n/k={} do not fit the fixed width of id1 or id3",
+            id1_max, nk
+        )));
+    }
+    // id2 is not padded beyond 3 digits, so its width is the width of the maximal value
+    let id2_max = id2_values
+        .as_ref()
+        .and_then(|values| values.iter().copied().max())
+        .unwrap_or(nk);
+    let id2_width = num_digits(id2_max, radix).max(3);
+
+    // String columns that are all NULL skip formatting and reserve no space for values,
+    // the legacy NULL check of exact_percent=False keeps some values even with 100
+    let all_null_strings = exact_percent && (nas_string >= 100);
+    let bytes_factor = if all_null_strings { 0 } else { 8 };
+    let mut id1_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, bytes_factor * (id1_width + 2)),
+    ); // id{:03}, utf8
+    let mut id2_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, bytes_factor * (id2_width + 2)),
+    ); // id{:03}, utf8
+    let mut id3_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, bytes_factor * (id3_width + 2)),
+    ); // id{:010}, utf8
+    let mut id4_builder = Int64Builder::with_capacity(item_capacity);
+    let mut id5_builder = Int64Builder::with_capacity(item_capacity);
+    let mut id6_builder = Int64Builder::with_capacity(item_capacity);
+    let mut v1_builder = Int64Builder::with_capacity(item_capacity);
+    let mut v2_builder = Int64Builder::with_capacity(item_capacity);
+    let mut v3_builder = Float64Builder::with_capacity(item_capacity);
+    let mut m1_builder = with_map.then(|| {
+        MapBuilder::new(
+            None,
+            StringBuilder::with_capacity(item_capacity * 2, item_capacity * 2),
+            Int64Builder::with_capacity(item_capacity * 2),
+        )
+    });
+    let mut dur_builder =
+        with_duration.then(|| DurationSecondBuilder::with_capacity(item_capacity));
+    let mut ts_builder =
+        monotonic_ts.then(|| TimestampMicrosecondBuilder::with_capacity(item_capacity));
+    let mut blob_builder = (blob_bytes > 0).then(|| {
+        LargeBinaryBuilder::with_capacity(
+            item_capacity,
+            safe_string_capacity(item_capacity, blob_bytes),
+        )
+    });
+    let mut blob = vec![0u8; blob_bytes];
+    let mut t1_builder = with_time.then(|| Time64MicrosecondBuilder::with_capacity(item_capacity));
+    let mut feat_builder = (feature_dim > 0).then(|| {
+        FixedSizeListBuilder::with_capacity(
+            Float64Builder::with_capacity(item_capacity * feature_dim),
+            feature_dim as i32,
+            item_capacity,
+        )
+    });
+    let mut tags_builder = with_string_list.then(|| {
+        ListBuilder::new(StringBuilder::with_capacity(
+            item_capacity * 3,
+            safe_string_capacity(item_capacity * 3, id1_width + 2),
+        ))
+    });
+    let mut ts = ts_start;
+    let column_seeds = column_seeds.unwrap_or_default();
+    if let Some(name) = column_seeds
+        .keys()
+        .find(|name| !GROUPBY_COLUMNS.contains(&name.as_str()))
+    {
+        return Err(PyValueError::new_err(format!(
+            "column_seeds keys should be from {:?}, but got {}",
+            GROUPBY_COLUMNS, name
+        )));
+    }
+    let mut column_rngs = GROUPBY_COLUMNS
+        .iter()
+        .map(|name| {
+            column_seeds
+                .get(*name)
+                .map(|seed| ChaCha8Rng::seed_from_u64(*seed))
+        })
+        .collect::<Vec<Option<ChaCha8Rng>>>();
+    // id and value columns may have their own streams, column_seeds take precedence
+    let mut key_rng = key_seed.map(ChaCha8Rng::seed_from_u64);
+    let mut value_rng = value_seed.map(ChaCha8Rng::seed_from_u64);
+    let id3_unique = if unique_id3 {
+        // Repeated rows and corrupted values would bring duplicates back
+        if (target_compressibility > 0.0) || (corrupt_target == Some("id3")) {
+            return Err(PyValueError::new_err(
+                "unique_id3 is not compatible with target_compressibility and corrupt_target=id3",
+            ));
+        }
+        if batch_size > nk {
+            return Err(PyValueError::new_err(format!(
+                "unique id3 requires batch_size <= n / k, but got {} > {}",
+                batch_size, nk
+            )));
+        }
+        let mut id3_pool = (1..=nk).collect::<Vec<i64>>();
+        let (chosen, _) = id3_pool.partial_shuffle(&mut *rng, item_capacity);
+        Some(chosen.to_vec())
+    } else {
+        None
+    };
+
+    let id1_groups = match &group_sizes {
+        Some(group_sizes) => {
+            if (group_sizes.len() as i64 != k)
+                || group_sizes.iter().any(|size| *size < 0)
+                || (group_sizes.iter().sum::<i64>() != batch_size)
+            {
+                return Err(PyValueError::new_err(format!(
+                    "group_sizes should have {} non-negative elements summing to {}, but got {:?}",
+                    k, batch_size, group_sizes
+                )));
+            }
+            // All the id columns are NULL with nas_string=100, so there are no groups to size,
+            // other options repeat, rewrite or drop rows after id1 is sized
+            if with_offset
+                || fast_nulls
+                || all_null_strings
+                || (target_compressibility > 0.0)
+                || messy_strings
+                || (corrupt_target == Some("id1"))
+                || filter.is_some()
+            {
+                return Err(PyValueError::new_err(
+                    "group_sizes is not compatible with with_offset, fast_nulls, nas_string=100, target_compressibility, messy_strings, corrupt_target=id1 and filter",
+                ));
+            }
+            let mut id1_pool = group_sizes
+                .iter()
+                .zip(1..=k)
+                .flat_map(|(size, group)| std::iter::repeat_n(group, *size as usize))
+                .collect::<Vec<i64>>();
+            id1_pool.shuffle(&mut *rng);
+            Some(id1_pool)
+        }
+        None => None,
+    };
+
+    let (mut main_id4_run, mut id4_run) = (None, None);
+    for row in 0..batch_size {
+        if index_hashed_keys {
+            // Keys of the row depend only on the seed, the row index and the column index,
+            // the index is the one in the output, after padding rows of with_offset
+            let row_seed = derive_seed(seed, (row - pad_rows) as u64);
+            for (column, column_rng) in column_rngs.iter_mut().take(6).enumerate() {
+                let column_seed = derive_seed(row_seed, column as u64);
+                *column_rng = Some(ChaCha8Rng::seed_from_u64(column_seed as u64));
+            }
+        }
+        // id1, string in form id123, 123 from 1-K
+        let (main_id1, id1) = if all_null_strings {
+            (None, None)
+        } else {
+            draw_column(
+                column_rngs[0].as_mut().or(key_rng.as_mut()),
+                rng,
+                nas_string,
+                nas_string,
+                |col_rng, nas| {
+                    // group_sizes dictate id1 without NULL checks
+                    if id1_groups.is_some() || fast_nulls || distr_nas.sample(col_rng) >= nas {
+                        Some(match &id1_groups {
+                            Some(id1_groups) => id1_groups[row as usize],
+                            None => distr_k.sample(col_rng),
+                        })
+                    } else {
+                        None
+                    }
+                },
+            )
+        };
+        match id1.map(|value| value + id1_offset) {
+            Some(number) if hex_ids => {
+                id1_builder.append_value(format!("id{:0w$x}", number, w = id1_width))
+            }
+            Some(number) => id1_builder.append_value(format!("id{:0w$}", number, w = id1_width)),
+            None if !all_null_strings => id1_builder.append_null(),
+            None => {}
+        }
+        // rows from null_bias_groups take NULLs in other id columns with their own rate
+        let row_nas = |id1: Option<i64>| match id1 {
+            Some(id1) if null_bias_groups.contains(&id1) => (null_bias_nas, null_bias_nas),
+            _ => (nas_string, nas_int),
+        };
+        let (main_nas_string, main_nas_int) = row_nas(main_id1);
+        let (row_nas_string, row_nas_int) = row_nas(id1);
+        if !all_null_strings {
+            // id2, string in form id123, 123 from 1-K or from id2_values
+            let (_, id2) = draw_column(
+                column_rngs[1].as_mut().or(key_rng.as_mut()),
+                rng,
+                main_nas_string,
+                row_nas_string,
+                |col_rng, nas| {
+                    if fast_nulls || distr_nas.sample(col_rng) >= nas {
+                        Some(match (&id2_values, &distr_id2_values) {
+                            (Some(values), Some(distr)) => values[distr.sample(col_rng)],
+                            _ => distr_nk.sample(col_rng),
+                        })
+                    } else {
+                        None
+                    }
+                },
+            );
+            match id2 {
+                Some(value) if hex_ids => id2_builder.append_value(format!("id{:03x}", value)),
+                Some(value) => id2_builder.append_value(format!("id{:03}", value)),
+                None => id2_builder.append_null(),
+            }
+            // id3, string in form id1234567890, number from 1-N/K
+            let (_, id3) = draw_column(
+                column_rngs[2].as_mut().or(key_rng.as_mut()),
+                rng,
+                main_nas_string,
+                row_nas_string,
+                |col_rng, nas| {
+                    if fast_nulls || distr_nas.sample(col_rng) >= nas {
+                        Some(match &id3_unique {
+                            Some(id3_unique) => id3_unique[row as usize],
+                            None => distr_nk.sample(col_rng),
+                        })
+                    } else {
+                        None
+                    }
+                },
+            );
+            match id3 {
+                Some(value) if hex_ids => {
+                    id3_builder.append_value(format!("id{:0w$x}", value, w = id3_width))
+                }
+                Some(value) => id3_builder.append_value(format!("id{:0w$}", value, w = id3_width)),
+                None => id3_builder.append_null(),
+            }
+        }
+        // id4, 1-K (multiplied by key_stride), int, resampled at the start of each run
+        if row % run_length == 0 {
+            (main_id4_run, id4_run) = draw_column(
+                column_rngs[3].as_mut().or(key_rng.as_mut()),
+                rng,
+                main_nas_int,
+                row_nas_int,
+                |col_rng, nas| {
+                    if fast_nulls || distr_nas.sample(col_rng) >= nas {
+                        Some(sample_key(pareto.as_ref(), &distr_k, k, col_rng) * key_stride)
+                    } else {
+                        None
+                    }
+                },
+            );
+        }
+        id4_builder.append_option(id4_run);
+        // id5, 1-K (multiplied by key_stride), int, equal to id4 with probability id4_id5_corr
+        let (_, id5) = draw_column(
+            column_rngs[4].as_mut().or(key_rng.as_mut()),
+            rng,
+            (main_nas_int, main_id4_run),
+            (row_nas_int, id4_run),
+            |col_rng, (nas, id4)| {
+                if fast_nulls || distr_nas.sample(col_rng) >= nas {
+                    Some(match id4 {
+                        Some(id4) if (id4_id5_corr > 0.0) && col_rng.random_bool(id4_id5_corr) => {
+                            id4
+                        }
+                        _ => sample_key(pareto.as_ref(), &distr_k, k, col_rng) * key_stride,
+                    })
+                } else {
+                    None
+                }
+            },
+        );
+        id5_builder.append_option(id5);
+        // id6, 1-N/K (multiplied by key_stride), int
+        let (_, id6) = draw_column(
+            column_rngs[5].as_mut().or(key_rng.as_mut()),
+            rng,
+            main_nas_int,
+            row_nas_int,
+            |col_rng, nas| {
+                if fast_nulls || distr_nas.sample(col_rng) >= nas {
+                    Some(sample_key(pareto.as_ref(), &distr_nk, nk, col_rng) * key_stride)
+                } else {
+                    None
+                }
+            },
+        );
+        id6_builder.append_option(id6);
+        // v1, 1-5, int
+        let (_, v1) = draw_column(
+            column_rngs[6].as_mut().or(value_rng.as_mut()),
+            rng,
+            (),
+            (),
+            |col_rng, _| distr_5.sample(col_rng),
+        );
+        v1_builder.append_value(v1);
+        // v2, 1-15, int
+        let (_, v2) = draw_column(
+            column_rngs[7].as_mut().or(value_rng.as_mut()),
+            rng,
+            (),
+            (),
+            |col_rng, _| distr_15.sample(col_rng),
+        );
+        v2_builder.append_value(v2);
+        // v3, random float, optionally rounded to N decimals or replaced by NaN
+        let (_, v3) = draw_column(
+            column_rngs[8].as_mut().or(value_rng.as_mut()),
+            rng,
+            (),
+            (),
+            |col_rng, _| {
+                let mut v3 = distr_float.sample(col_rng);
+                if let Some(levels) = v3_levels {
+                    // The center of the bin, 100.0 falls into the last bin
+                    v3 = ((v3 / 100.0 * levels).floor().min(levels - 1.0) + 0.5) * 100.0 / levels;
+                }
+                if (v3_nan_rate > 0.0) && col_rng.random_bool(v3_nan_rate) {
+                    f64::NAN
+                } else if (v3_edge_rate > 0.0) && col_rng.random_bool(v3_edge_rate) {
+                    V3_EDGE_VALUES[col_rng.random_range(0..V3_EDGE_VALUES.len())]
+                } else {
+                    match v3_scale {
+                        Some(scale) => (v3 * scale).round() / scale,
+                        None => v3,
+                    }
+                }
+            },
+        );
+        v3_builder.append_value(v3);
+        // m1, map {"a": 1-5, "b": 1-15}
+        if let Some(m1_builder) = m1_builder.as_mut() {
+            m1_builder.keys().append_value("a");
+            m1_builder.values().append_value(distr_5.sample(&mut *rng));
+            m1_builder.keys().append_value("b");
+            m1_builder.values().append_value(distr_15.sample(&mut *rng));
+            m1_builder.append(true).map_err(ArrowError)?;
+        }
+        // dur, dur_min-dur_max, duration in seconds
+        if let Some(dur_builder) = dur_builder.as_mut() {
+            if distr_nas.sample(&mut *rng) >= nas {
+                dur_builder.append_value(distr_dur.sample(&mut *rng))
+            } else {
+                dur_builder.append_null()
+            }
+        }
+        // ts, strictly increasing timestamp with a step from 1us to 1s
+        if let Some(ts_builder) = ts_builder.as_mut() {
+            if row > 0 {
+                ts += distr_ts_step.sample(&mut *rng);
+            }
+            ts_builder.append_value(ts);
+        }
+        // blob, blob_bytes of random bytes
+        if let Some(blob_builder) = blob_builder.as_mut() {
+            if distr_nas.sample(&mut *rng) >= nas {
+                rng.fill(&mut blob[..]);
+                blob_builder.append_value(&blob)
+            } else {
+                blob_builder.append_null()
+            }
+        }
+        // feat, feature_dim uniform floats from [0, 1)
+        if let Some(feat_builder) = feat_builder.as_mut() {
+            for _ in 0..feature_dim {
+                feat_builder.values().append_value(rng.random::<f64>());
+            }
+            feat_builder.append(true);
+        }
+        // t1, time of the day in microseconds
+        if let Some(t1_builder) = t1_builder.as_mut() {
+            if distr_nas.sample(&mut *rng) >= nas {
+                t1_builder.append_value(distr_time.sample(&mut *rng))
+            } else {
+                t1_builder.append_null()
+            }
+        }
+        // tags, list of 0-3 id{:03} values
+        if let Some(tags_builder) = tags_builder.as_mut() {
+            if distr_nas.sample(&mut *rng) >= nas {
+                for _ in 0..rng.random_range(0..=3) {
+                    tags_builder
+                        .values()
+                        .append_value(format!("id{:03}", distr_k.sample(&mut *rng)));
+                }
+                tags_builder.append(true)
+            } else {
+                tags_builder.append_null()
+            }
+        }
+    }
+
+    if all_null_strings {
+        id1_builder.append_nulls(item_capacity);
+        id2_builder.append_nulls(item_capacity);
+        id3_builder.append_nulls(item_capacity);
+    }
+
+    let mut fields = vec![
+        Field::new("id1", DataType::Utf8, true),
+        Field::new("id2", DataType::Utf8, true),
+        Field::new("id3", DataType::Utf8, true),
+        Field::new("id4", DataType::Int64, true),
+        Field::new("id5", DataType::Int64, true),
+        Field::new("id6", DataType::Int64, true),
+        Field::new("v1", DataType::Int64, false),
+        Field::new("v2", DataType::Int64, false),
+        Field::new("v3", DataType::Float64, false),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(id1_builder.finish()),
+        Arc::new(id2_builder.finish()),
+        Arc::new(id3_builder.finish()),
+        Arc::new(id4_builder.finish()),
+        Arc::new(id5_builder.finish()),
+        Arc::new(id6_builder.finish()),
+        Arc::new(v1_builder.finish()),
+        Arc::new(v2_builder.finish()),
+        Arc::new(v3_builder.finish()),
+    ];
+    if let Some(ts_builder) = ts_builder.as_ref().filter(|_| seasonal) {
+        let v3 = columns[8].as_primitive::<Float64Type>();
+        let v3_seasonal = v3
+            .values()
+            .iter()
+            .zip(ts_builder.values_slice())
+            .map(|(value, ts)| {
+                // Bits are compared, so a generated 0.0 is not taken for the -0.0 edge value
+                if value.is_nan()
+                    || V3_EDGE_VALUES
+                        .iter()
+                        .any(|e| e.to_bits() == value.to_bits())
+                {
+                    return *value;
+                }
+                // 1970-01-01 is Thursday, so the day of week counted from Monday is shifted by 3
+                let dow = (ts.div_euclid(86_400_000_000) + 3).rem_euclid(7);
+                let value =
+                    value * (1.0 + 0.5 * (2.0 * std::f64::consts::PI * dow as f64 / 7.0).sin());
+                match v3_scale {
+                    Some(scale) => (value * scale).round() / scale,
+                    None => value,
+                }
+            })
+            .collect::<Float64Array>();
+        columns[8] = Arc::new(v3_seasonal);
+    }
+    if float16 {
+        let v3 = columns[8].as_primitive::<Float64Type>();
+        let mut v3_f16_builder = Float16Builder::with_capacity(v3.len());
+        for value in v3.values() {
+            v3_f16_builder.append_value(f16::from_f64(*value));
+        }
+        fields[8] = Field::new("v3", DataType::Float16, false);
+        columns[8] = Arc::new(v3_f16_builder.finish());
+    }
+    if decimal256 {
+        let v3 = columns[8].as_primitive::<Float64Type>();
+        let mut v3_decimal_builder = Decimal256Builder::with_capacity(v3.len())
+            .with_precision_and_scale(V3_DECIMAL_PRECISION, V3_DECIMAL_SCALE)
+            .map_err(ArrowError)?;
+        let scale = 10f64.powi(V3_DECIMAL_SCALE as i32);
+        for value in v3.values() {
+            v3_decimal_builder.append_value(i256::from_i128((value * scale).round() as i128));
+        }
+        let v3_decimal = v3_decimal_builder.finish();
+        fields[8] = Field::new("v3", v3_decimal.data_type().clone(), false);
+        columns[8] = Arc::new(v3_decimal);
+    }
+    if fast_nulls {
+        for (i, column) in columns.iter_mut().take(6).enumerate() {
+            if (i < 3) && all_null_strings {
+                continue;
+            }
+            let column_nas = if i < 3 { nas_string } else { nas_int };
+            *column = with_sampled_nulls(column, column_nas, &distr_nas, &mut *rng)
+                .map_err(ArrowError)?;
+        }
+    }
+
+    if let (Some(sentinel), Some(int_sentinel)) = (&null_sentinel, int_sentinel) {
+        for (i, column) in columns.iter_mut().take(6).enumerate() {
+            *column = if i < 3 {
+                Arc::new(
+                    column
+                        .as_string::<i32>()
+                        .iter()
+                        .map(|v| Some(v.unwrap_or(sentinel)))
+                        .collect::<StringArray>(),
+                )
+            } else {
+                Arc::new(
+                    column
+                        .as_primitive::<Int64Type>()
+                        .iter()
+                        .map(|v| Some(v.unwrap_or(int_sentinel)))
+                        .collect::<Int64Array>(),
+                )
+            };
+        }
+    }
+
+    if target_compressibility > 0.0 {
+        // A row either keeps its index or takes the source of the previous row
+        let mut indices = Vec::with_capacity(item_capacity);
+        for i in 0..item_capacity as u32 {
+            let repeat = (i > 0) && rng.random_bool(target_compressibility);
+            indices.push(if repeat { indices[i as usize - 1] } else { i });
+        }
+        let indices = UInt32Array::from(indices);
+        for column in columns.iter_mut().take(9) {
+            *column = take(column, &indices, None).map_err(ArrowError)?;
+        }
+    }
+
+    if let Some(index) = corrupt_index {
+        let column = &columns[index];
+        let amount = (corrupt_rate * column.len() as f64).round() as usize;
+        let mut mask = vec![false; column.len()];
+        for row in rand::seq::index::sample(&mut *rng, column.len(), amount) {
+            mask[row] = true;
+        }
+        let corrupt: ArrayRef = match column.data_type() {
+            DataType::Utf8 => Arc::new(StringArray::from(vec![""])),
+            DataType::Int64 => Arc::new(Int64Array::from(vec![CORRUPT_INT])),
+            // v3 may be Float64 or Float16
+            data_type => {
+                cast(&Float64Array::from(vec![CORRUPT_FLOAT]), data_type).map_err(ArrowError)?
+            }
+        };
+        columns[index] =
+            zip(&BooleanArray::from(mask), &Scalar::new(corrupt), column).map_err(ArrowError)?;
+    }
+
+    if messy_strings {
+        let id1 = columns[0]
+            .as_string::<i32>()
+            .iter()
+            .map(|value| {
+                let value = value?;
+                if !rng.random_bool(messy_rate) {
+                    return Some(value.to_string());
+                }
+                Some(match rng.random_range(0..4) {
+                    0 => format!(" {}", value),
+                    1 => format!("{} ", value),
+                    2 => format!(" {} ", value),
+                    _ => value.to_uppercase(),
+                })
+            })
+            .collect::<StringArray>();
+        columns[0] = Arc::new(id1);
+    }
+
+    if binary_id3 {
+        let id3 = columns[2].as_string::<i32>();
+        let mut id3_binary_builder =
+            BinaryBuilder::with_capacity(id3.len(), id3.value_data().len());
+        for value in id3.iter() {
+            id3_binary_builder.append_option(value.map(str::as_bytes));
+        }
+        fields[2] = Field::new("id3", DataType::Binary, true);
+        columns[2] = Arc::new(id3_binary_builder.finish());
+    }
+
+    if let Some(mut m1_builder) = m1_builder {
+        let m1_array = m1_builder.finish();
+        fields.push(Field::new("m1", m1_array.data_type().clone(), false));
+        columns.push(Arc::new(m1_array));
+    }
+    if let Some(mut dur_builder) = dur_builder {
+        fields.push(Field::new(
+            "dur",
+            DataType::Duration(TimeUnit::Second),
+            true,
+        ));
+        columns.push(Arc::new(dur_builder.finish()));
+    }
+    if let Some(mut ts_builder) = ts_builder {
+        fields.push(Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ));
+        columns.push(Arc::new(ts_builder.finish()));
+    }
+    if let Some(mut blob_builder) = blob_builder {
+        fields.push(Field::new("blob", DataType::LargeBinary, true));
+        columns.push(Arc::new(blob_builder.finish()));
+    }
+    if let Some(mut feat_builder) = feat_builder {
+        let feat_array = feat_builder.finish();
+        fields.push(Field::new("feat", feat_array.data_type().clone(), false));
+        columns.push(Arc::new(feat_array));
+    }
+    if let Some(mut t1_builder) = t1_builder {
+        fields.push(Field::new(
+            "t1",
+            DataType::Time64(TimeUnit::Microsecond),
+            true,
+        ));
+        columns.push(Arc::new(t1_builder.finish()));
+    }
+    if let Some(mut tags_builder) = tags_builder {
+        let tags_array = tags_builder.finish();
+        fields.push(Field::new("tags", tags_array.data_type().clone(), true));
+        columns.push(Arc::new(tags_array));
+    }
+
+    let mut batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+    if with_offset {
+        // Padding rows are dropped before any reordering or filtering can move them
+        batch = batch.slice(pad_rows as usize, batch.num_rows() - pad_rows as usize);
+    }
+
+    if cumulative_v1 {
+        batch = sort_by_keys(&batch, &["id1"]).map_err(ArrowError)?;
+        let id1 = batch.column(0).as_string::<i32>();
+        let v1 = batch.column(6).as_primitive::<Int64Type>();
+        let mut previous = None;
+        let mut sum = 0;
+        let v1_cumulative = id1
+            .iter()
+            .zip(v1.values().iter())
+            .map(|(key, value)| {
+                // The sum restarts at the first row of each contiguous id1 block
+                if previous != Some(key) {
+                    previous = Some(key);
+                    sum = 0;
+                }
+                sum += value;
+                sum
+            })
+            .collect::<Int64Array>();
+        let mut columns = batch.columns().to_vec();
+        columns[6] = Arc::new(v1_cumulative);
+        batch = RecordBatch::try_new(batch.schema(), columns).map_err(ArrowError)?;
+    }
+
+    if let Some(sort_by) = &sort_by {
+        if let Some(name) = sort_by
+            .iter()
+            .find(|name| batch.schema().column_with_name(name).is_none())
+        {
+            return Err(PyValueError::new_err(format!(
+                "sort_by columns should exist in the output, but got {}",
+                name
+            )));
+        }
+        let keys = sort_by
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<&str>>();
+        batch = sort_by_keys(&batch, &keys).map_err(ArrowError)?;
+    }
+
+    if shuffle {
+        let mut indices = (0..batch.num_rows() as u32).collect::<Vec<u32>>();
+        indices.shuffle(&mut *rng);
+        batch = take_record_batch(&batch, &UInt32Array::from(indices)).map_err(ArrowError)?;
+    }
+
+    if with_checksum {
+        let row_hash = row_checksums(&batch).map_err(ArrowError)?;
+        let mut fields = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .collect::<Vec<Field>>();
+        fields.push(Field::new("row_hash", DataType::Int64, false));
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(row_hash));
+        batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(ArrowError)?;
+    }
+
+    // After every optional column is appended, so row_hash is nullable as well
+    if force_nullable {
+        let fields = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone().with_nullable(true))
+            .collect::<Vec<Field>>();
+        batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), batch.columns().to_vec())
+            .map_err(ArrowError)?;
+    }
+
+    if let Some(column_order) = &column_order {
+        let schema = batch.schema();
+        let indices = column_order
+            .iter()
+            .map(|name| schema.index_of(name).ok())
+            .collect::<Option<Vec<usize>>>()
+            .filter(|indices| {
+                indices.len() == schema.fields().len()
+                    && indices.iter().collect::<HashSet<_>>().len() == indices.len()
+            })
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "column_order should be a permutation of {:?}, but got {:?}",
+                    schema
+                        .fields()
+                        .iter()
+                        .map(|field| field.name().as_str())
+                        .collect::<Vec<&str>>(),
+                    column_order
+                ))
+            })?;
+        batch = batch.project(&indices).map_err(ArrowError)?;
+    }
+
+    if let Some((column, op, value)) = &predicate {
+        let values =
+            cast(batch.column_by_name(column).unwrap(), &DataType::Float64).map_err(ArrowError)?;
+        let mask = values
+            .as_primitive::<Float64Type>()
+            .iter()
+            .map(|v| {
+                v.map(|v| match op.as_str() {
+                    "=" => v == *value,
+                    "!=" => v != *value,
+                    "<" => v < *value,
+                    "<=" => v <= *value,
+                    ">" => v > *value,
+                    _ => v >= *value,
+                })
+            })
+            .collect::<BooleanArray>();
+        batch = filter_record_batch(&batch, &mask).map_err(ArrowError)?;
+    }
+
+    if let Some(field_metadata) = field_metadata {
+        let schema = batch.schema();
+        if let Some(name) = field_metadata
+            .keys()
+            .find(|name| schema.column_with_name(name).is_none())
+        {
+            return Err(PyValueError::new_err(format!(
+                "field_metadata columns should exist in the output, but got {}",
+                name
+            )));
+        }
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| match field_metadata.get(field.name()) {
+                Some(metadata) => field.as_ref().clone().with_metadata(metadata.clone()),
+                None => field.as_ref().clone(),
+            })
+            .collect::<Vec<Field>>();
+        batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), batch.columns().to_vec())
+            .map_err(ArrowError)?;
+    }
+
+    Ok(batch)
+}
+
+/**
+Export the state of the random generator that generate_groupby uses for the given seed.
+The state can be passed to generate_groupby_from_state to reproduce the batch exactly.
+
+:param seed: int
+    A random seed value.
+
+:return: bytes
+*/
+#[pyfunction]
+fn groupby_rng_state(seed: i64) -> Vec<u8> {
+    rng_to_state(&ChaCha8Rng::seed_from_u64(seed as u64))
+}
+
+/**
+Generate H2O group-by dataset from the explicit state of the random generator.
+Passing the state exported by groupby_rng_state(seed) gives exactly the same
+batch as generate_groupby with that seed.
+
+:param state_bytes: bytes
+    A state of the random generator, see groupby_rng_state.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param batch_size: int
+    A size of the output batch.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn generate_groupby_from_state(
+    state_bytes: &[u8],
+    n: i64,
+    k: i64,
+    nas: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batch = groupby_batch(n, k, nas, 0, batch_size, None, Some(state_bytes))?;
+    Ok(PyArrowType(batch))
+}
+
+/// Derive a seed of the batch from the base seed and the batch index (SplitMix64 mixing).
+fn derive_seed(seed: i64, index: u64) -> i64 {
+    let mut z = (seed as u64).wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    ((z ^ (z >> 31)) >> 1) as i64
+}
+
+/// Split n rows into batches of batch_size rows, returning (seed, size) per batch.
+/// Without a salt all the batches share the same seed, like calls of generate_groupby.
+fn split_batches(
+    n: i64,
+    seed: i64,
+    batch_size: i64,
+    batch_salt: bool,
+) -> PyResult<Vec<(i64, i64)>> {
+    if (n <= 0) || (batch_size <= 0) {
+        return Err(PyValueError::new_err(format!(
+            "n and batch_size should be positive, but got n={} and batch_size={}",
+            n, batch_size
+        )));
+    }
+    let mut batches = Vec::with_capacity((n / batch_size + 1) as usize);
+    let mut remaining = n;
+    while remaining > 0 {
+        let size = remaining.min(batch_size);
+        let batch_seed = if batch_salt {
+            derive_seed(seed, batches.len() as u64)
+        } else {
+            seed
+        };
+        batches.push((batch_seed, size));
+        remaining -= size;
+    }
+    Ok(batches)
+}
+
+/// Running totals over the batches produced by a stream:
+/// rows, batches and NULLs per column name.
+#[pyclass(get_all)]
+#[derive(Clone, Default)]
+struct GenerationStats {
+    rows: i64,
+    batches: i64,
+    nulls: BTreeMap<String, i64>, // column name -> number of NULLs
+}
+
+impl GenerationStats {
+    fn update(&mut self, batch: &RecordBatch) {
+        self.rows += batch.num_rows() as i64;
+        self.batches += 1;
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            *self.nulls.entry(field.name().clone()).or_insert(0) += column.null_count() as i64;
+        }
+    }
+}
+
+#[pymethods]
+impl GenerationStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "GenerationStats(rows={}, batches={}, nulls={:?})",
+            self.rows, self.batches, self.nulls
+        )
+    }
+}
+
+/// A lazy iterator over group-by batches of the dataset of n rows.
+/// Each batch is generated only when requested by the consumer.
+struct GroupByReader {
+    n: i64,
+    k: i64,
+    nas: i64,
+    batches: std::vec::IntoIter<(i64, i64)>, // (seed, size) per batch
+    next_ts: Option<i64>,                    // the start of the monotonic ts for the next batch
+    first: Option<RecordBatch>,
+    schema: SchemaRef,
+    stats: GenerationStats, // accumulated over the batches returned so far
+    id1_dictionary: Option<ArrayRef>, // all the id1 values for 1..=k if the dictionary is shared
+}
+
+impl GroupByReader {
+    #[allow(clippy::too_many_arguments)]
+    fn try_new(
+        n: i64,
+        k: i64,
+        nas: i64,
+        seed: i64,
+        batch_size: i64,
+        ts_start: Option<i64>,
+        batch_salt: bool,
+        shared_dictionary: bool,
+    ) -> PyResult<Self> {
+        let batches = split_batches(n, seed, batch_size, batch_salt)?;
+        let id1_dictionary = if shared_dictionary {
+            if k > i32::MAX as i64 {
+                return Err(PyValueError::new_err(format!(
+                    "shared_dictionary requires k <= {}, but got {}",
+                    i32::MAX,
+                    k
+                )));
+            }
+            let width = num_digits(k, 10).max(3);
+            let values = StringArray::from_iter_values(
+                (1..=k).map(|value| format!("id{:0w$}", value, w = width)),
+            );
+            Some(Arc::new(values) as ArrayRef)
+        } else {
+            None
+        };
+
+        let mut reader = GroupByReader {
+            n,
+            k,
+            nas,
+            batches: batches.into_iter(),
+            next_ts: ts_start,
+            first: None,
+            schema: Arc::new(Schema::empty()),
+            stats: GenerationStats::default(),
+            id1_dictionary,
+        };
+        // The first batch is generated eagerly to know the schema
+        let first = reader.next_batch().unwrap()?;
+        reader.schema = first.schema();
+        reader.first = Some(first);
+
+        Ok(reader)
+    }
+
+    fn next_batch(&mut self) -> Option<PyResult<RecordBatch>> {
+        let (seed, size) = self.batches.next()?;
+        let batch = groupby_batch(self.n, self.k, self.nas, seed, size, self.next_ts, None);
+        if let (Ok(batch), Some(_)) = (&batch, self.next_ts) {
+            // Carry the last timestamp across batches to keep ts strictly increasing
+            let ts = batch.column_by_name("ts").unwrap();
+            let ts = ts.as_primitive::<TimestampMicrosecondType>();
+            self.next_ts = Some(ts.value(ts.len() - 1) + 1);
+        }
+        match (batch, &self.id1_dictionary) {
+            (Ok(batch), Some(dictionary)) => Some(
+                encode_with_dictionary(&batch, "id1", dictionary)
+                    .map_err(|e| PyErr::from(ArrowError(e))),
+            ),
+            (batch, _) => Some(batch),
+        }
+    }
+
+    /// Return the next batch of the stream and account it in stats.
+    fn next_recorded(&mut self) -> Option<PyResult<RecordBatch>> {
+        let batch = match self.first.take() {
+            Some(first) => Ok(first),
+            None => self.next_batch()?,
+        };
+        if let Ok(batch) = &batch {
+            self.stats.update(batch);
+        }
+        Some(batch)
+    }
+}
+
+impl Iterator for GroupByReader {
+    type Item = Result<RecordBatch, arrow::error::ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.next_recorded()?
+                .map_err(|e| arrow::error::ArrowError::ExternalError(Box::new(e))),
+        )
+    }
+}
+
+/// A Python iterator over group-by batches that keeps statistics of the stream.
+#[pyclass]
+struct GroupByStream {
+    reader: GroupByReader,
+}
+
+#[pymethods]
+impl GroupByStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<PyArrowType<RecordBatch>>> {
+        self.reader
+            .next_recorded()
+            .transpose()
+            .map(|batch| batch.map(PyArrowType))
+    }
+
+    #[getter]
+    fn schema(&self) -> PyArrowType<Schema> {
+        PyArrowType(self.reader.schema.as_ref().clone())
+    }
+
+    /// Statistics (rows, batches, NULLs per column) of all the batches returned so far.
+    fn stats(&self) -> GenerationStats {
+        self.reader.stats.clone()
+    }
+}
+
+/// Size of the data of the batch in bytes: lengths of buffers without spare capacity.
+fn estimated_batch_bytes(batch: &RecordBatch) -> Result<usize, arrow::error::ArrowError> {
+    batch
+        .columns()
+        .iter()
+        .map(|column| column.to_data().get_slice_memory_size())
+        .sum()
+}
+
+/// An amount of rows of the probe batch used for the initial estimation of the row size.
+const BYTE_BUDGET_PROBE_ROWS: i64 = 1024;
+
+/**
+A Python iterator over group-by batches sized to a target byte budget instead of rows.
+The amount of rows of each batch is the budget divided by the estimated size of a row.
+The estimation starts from a probe batch and is refined by every generated batch,
+so batches are close to the budget; the last batch covers the remaining rows.
+Seeds of batches are derived from the seed like in generate_groupby_reader with batch_salt.
+
+:param n: int
+    A total amount of rows.
+:param k: int
+    A number of groups.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param target_bytes: int
+    A target size of each batch in bytes. Should be positive.
+*/
+#[pyclass]
+struct GroupByByteBudgetReader {
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    target_bytes: i64,
+    remaining: i64, // rows that are not generated yet
+    index: u64,     // the index of the next batch
+    #[pyo3(get)]
+    estimated_row_bytes: f64, // refined after each batch
+}
+
+#[pymethods]
+impl GroupByByteBudgetReader {
+    #[new]
+    fn new(n: i64, k: i64, nas: i64, seed: i64, target_bytes: i64) -> PyResult<Self> {
+        if (n <= 0) || (target_bytes <= 0) {
+            return Err(PyValueError::new_err(format!(
+                "n and target_bytes should be positive, but got n={} and target_bytes={}",
+                n, target_bytes
+            )));
+        }
+        let probe_rows = n.min(BYTE_BUDGET_PROBE_ROWS);
+        let probe = groupby_batch(n, k, nas, seed, probe_rows, None, None)?;
+        let probe_bytes = estimated_batch_bytes(&probe).map_err(ArrowError)?;
+        Ok(GroupByByteBudgetReader {
+            n,
+            k,
+            nas,
+            seed,
+            target_bytes,
+            remaining: n,
+            index: 0,
+            estimated_row_bytes: probe_bytes as f64 / probe_rows as f64,
+        })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<PyArrowType<RecordBatch>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let rows =
+            ((self.target_bytes as f64 / self.estimated_row_bytes) as i64).clamp(1, self.remaining);
+        let batch_seed = derive_seed(self.seed, self.index);
+        let batch = groupby_batch(self.n, self.k, self.nas, batch_seed, rows, None, None)?;
+        let bytes = estimated_batch_bytes(&batch).map_err(ArrowError)?;
+        self.estimated_row_bytes = bytes as f64 / rows as f64;
+        self.remaining -= rows;
+        self.index += 1;
+        Ok(Some(PyArrowType(batch)))
+    }
+}
+
+/**
+A Python iterator over batches of two datasets interleaved for mixed-workload benchmarks:
+group-by batches (see generate_groupby) alternate with join lhs batches
+(see generate_join_lhs), each dataset having n rows and its own schema.
+Keys of join batches are sampled uniformly from the key spaces of join tables.
+When one dataset is exhausted, the rest of the other one is yielded.
+Seeds of batches are derived from the seed and the index of the batch in the stream.
+
+:param n: int
+    An amount of rows of each dataset. Should be positive.
+:param k: int
+    A number of groups of the group-by dataset.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs of the group-by dataset.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch except the last ones. Should be positive.
+
+:return: tuple[str, pyarrow.RecordBatch]
+    Pairs of the name of the dataset ("groupby" or "join_lhs") and a batch.
+*/
+#[pyclass]
+struct MixedWorkloadReader {
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    remaining_groupby: i64, // group-by rows that are not generated yet
+    remaining_join: i64,    // join lhs rows that are not generated yet
+    index: u64,             // the index of the next batch in the stream
+}
+
+#[pymethods]
+impl MixedWorkloadReader {
+    #[new]
+    fn new(n: i64, k: i64, nas: i64, seed: i64, batch_size: i64) -> PyResult<Self> {
+        if (n <= 0) || (batch_size <= 0) {
+            return Err(PyValueError::new_err(format!(
+                "n and batch_size should be positive, but got n={} and batch_size={}",
+                n, batch_size
+            )));
+        }
+        Ok(MixedWorkloadReader {
+            n,
+            k,
+            nas,
+            seed,
+            batch_size,
+            remaining_groupby: n,
+            remaining_join: n,
+            index: 0,
+        })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<(String, PyArrowType<RecordBatch>)>> {
+        let groupby_turn = self.index.is_multiple_of(2) || (self.remaining_join == 0);
+        let batch_seed = derive_seed(self.seed, self.index);
+        if groupby_turn && (self.remaining_groupby > 0) {
+            let rows = self.batch_size.min(self.remaining_groupby);
+            let batch = groupby_batch(self.n, self.k, self.nas, batch_seed, rows, None, None)?;
+            self.remaining_groupby -= rows;
+            self.index += 1;
+            return Ok(Some(("groupby".to_string(), PyArrowType(batch))));
+        }
+        if self.remaining_join == 0 {
+            return Ok(None);
+        }
+        let rows = self.batch_size.min(self.remaining_join);
+        let mut rng = ChaCha8Rng::seed_from_u64(batch_seed as u64);
+        let mut keys = Vec::with_capacity(3);
+        for nn in [self.n / 1_000_000, self.n / 1_000, self.n] {
+            let distr_key = Uniform::<i64>::try_from(1..=join_key_levels(nn).max(1))
+                .map_err(|e| UniformError(e))?;
+            let values = (0..rows)
+                .map(|_| distr_key.sample(&mut rng))
+                .collect::<Int64Array>();
+            keys.push(PyArrowType(values.into_data()));
+        }
+        let (k3, k2, k1) = (
+            keys.pop().unwrap(),
+            keys.pop().unwrap(),
+            keys.pop().unwrap(),
+        );
+        let batch = generate_join_lhs(
+            self.n, batch_seed, k1, k2, k3, rows, false, false, false, 1.0, 100.0, "uniform",
+        )?;
+        self.remaining_join -= rows;
+        self.index += 1;
+        Ok(Some(("join_lhs".to_string(), batch)))
+    }
+}
+
+impl RecordBatchReader for GroupByReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Generate a group-by batch with all the optional parameters set to defaults.
+/// If ts_start is passed, the monotonic ts column is generated starting from it.
+/// If rng_state is passed, the random generator is restored from it instead of the seed.
+fn groupby_batch(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    ts_start: Option<i64>,
+    rng_state: Option<&[u8]>,
+) -> PyResult<RecordBatch> {
+    groupby_with_params(GroupByParams {
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        monotonic_ts: ts_start.is_some(),
+        ts_start: ts_start.unwrap_or(0),
+        rng_state,
+        ..Default::default()
+    })
+}
+
+/**
+Generate H2O group-by dataset as a stream of batches.
+Batches are generated lazily, one by one, when the consumer requests them.
+The result supports the Arrow C stream interface, so it can be passed
+directly to engines that accept pyarrow.RecordBatchReader (DuckDB, Polars, etc.).
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch except the last one. Should be positive.
+:param monotonic_ts: bool
+    If true, the strictly increasing ts column is appended.
+    The last value is carried across batches, so ts is increasing in the whole stream.
+:param ts_start: int
+    The first value of ts in microseconds since the epoch, 0 by default.
+:param batch_salt: bool
+    If true, the index of the batch is mixed into the seed, so batches are distinct
+    but the whole stream is still reproducible. By default all the batches
+    are generated with the same seed and are identical, like calls of generate_groupby.
+:param shared_dictionary: bool
+    If true, id1 is emitted as Dictionary(Int32, Utf8) with the same dictionary of all
+    the values for 1..=k in every batch, so codes are comparable across batches.
+
+:return: pyarrow.RecordBatchReader
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    n,
+    k,
+    nas,
+    seed,
+    batch_size,
+    monotonic_ts=false,
+    ts_start=0,
+    batch_salt=false,
+    shared_dictionary=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_reader(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    monotonic_ts: bool,
+    ts_start: i64,
+    batch_salt: bool,
+    shared_dictionary: bool,
+) -> PyResult<PyArrowType<Box<dyn RecordBatchReader + Send>>> {
+    let reader = GroupByReader::try_new(
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        monotonic_ts.then_some(ts_start),
+        batch_salt,
+        shared_dictionary,
+    )?;
+    Ok(PyArrowType(Box::new(reader)))
+}
+
+/**
+Create a stream of group-by batches like generate_groupby_reader does, but as an iterator
+that accumulates statistics of the returned batches. After iteration stats() reports
+the total number of rows and NULLs per column without re-scanning batches in Python.
+
+:param n: int
+    A total amount of rows.
+:param k: int
+    A number of groups.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch except the last one. Should be positive.
+:param monotonic_ts: bool
+    If true, the strictly increasing ts column is appended and carried across batches.
+:param ts_start: int
+    The first value of ts in microseconds since the epoch, 0 by default.
+:param batch_salt: bool
+    If true, the index of the batch is mixed into the seed, so batches are distinct.
+:param shared_dictionary: bool
+    If true, id1 is emitted as Dictionary(Int32, Utf8) shared by all the batches.
+
+:return: GroupByStream
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    n,
+    k,
+    nas,
+    seed,
+    batch_size,
+    monotonic_ts=false,
+    ts_start=0,
+    batch_salt=false,
+    shared_dictionary=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_stream(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    monotonic_ts: bool,
+    ts_start: i64,
+    batch_salt: bool,
+    shared_dictionary: bool,
+) -> PyResult<GroupByStream> {
+    let reader = GroupByReader::try_new(
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        monotonic_ts.then_some(ts_start),
+        batch_salt,
+        shared_dictionary,
+    )?;
+    Ok(GroupByStream { reader })
+}
+
+/**
+Generate one shard of H2O group-by dataset split into shards for distributed generation.
+n rows are split into contiguous shards: each shard has n / shards rows and the first
+n % shards shards have one more row. Each shard is generated from its own seed derived
+from the seed and shard_index, so shards can be generated independently in any order.
+
+:param n: int
+    A total amount of rows in all the shards. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value of the whole dataset.
+:param shards: int
+    An amount of shards. Should be positive and not greater than n.
+:param shard_index: int
+    The index of the shard to generate, from [0, shards).
+:param global_seq: bool
+    If true, an additional column id0 of type Int64 is appended with the global row number:
+    shards hold contiguous ranges, so concatenated shards give exactly 0, 1, ..., n - 1.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+#[pyo3(signature = (n, k, nas, seed, shards, shard_index, global_seq=false))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_shard(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    shards: i64,
+    shard_index: i64,
+    global_seq: bool,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    if (shards <= 0) || (shards > n) {
+        return Err(PyValueError::new_err(format!(
+            "shards should be in [1, {}], but got {}",
+            n, shards
+        )));
+    }
+    if !(0..shards).contains(&shard_index) {
+        return Err(PyValueError::new_err(format!(
+            "shard_index should be in [0, {}), but got {}",
+            shards, shard_index
+        )));
+    }
+    // The first n % shards shards take one extra row
+    let (base, extra) = (n / shards, n % shards);
+    let rows = base + (shard_index < extra) as i64;
+    let offset = shard_index * base + shard_index.min(extra);
+
+    let shard_seed = derive_seed(seed, shard_index as u64);
+    let mut batch = groupby_batch(n, k, nas, shard_seed, rows, None, None)?;
+
+    if global_seq {
+        let id0 = Int64Array::from_iter_values(offset..offset + rows);
+        let mut fields = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .collect::<Vec<Field>>();
+        fields.push(Field::new("id0", DataType::Int64, false));
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(id0));
+        batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(ArrowError)?;
+    }
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Count 32-bit words of the random stream that generate_groupby consumes per row for the given options.
+It describes the layout of the random stream: draws of the row go in the order
+of columns id1, ..., id6, v1, v2, v3 and then optional columns. A draw of a value, a NULL check
+or a probability takes a u64, that is 2 words; filling a blob takes ceil(blob_bytes / 4) words.
+Each id column takes a draw for the NULL check and a draw for the value;
+a NULL cell skips the value draw, so the count is exact for rows without NULLs.
+Draws made after the loop over rows are counted per row if they are made per row
+(fast_nulls validity, target_compressibility). shuffle is not counted, because the
+permutation takes a variable amount of batched 32-bit draws after all the rows.
+key_dist and own streams of columns (column_seeds, key_seed, value_seed, index_hashed_keys)
+do not change the count. Options skipping draws in some rows (run_length, group_sizes)
+are not supported.
+
+:param nas_enabled: bool
+    If NULLs are generated (nas, nas_string or nas_int are positive).
+:param fast_nulls: bool
+    If the fast_nulls path is used. Values of id columns are drawn without NULL checks
+    and validity is drawn after the loop if NULLs are enabled.
+:param v3_nan: bool
+    If v3_nan_rate is positive.
+:param id4_id5_corr: bool
+    If id4_id5_corr is positive. id5 equal to id4 skips the value draw.
+:param with_map: bool
+    If the m1 column is generated.
+:param with_duration: bool
+    If the dur column is generated.
+:param monotonic_ts: bool
+    If the ts column is generated. The first row of the batch does not take a draw.
+:param blob_bytes: int
+    A size of the blob column, 0 if the column is not generated.
+:param feature_dim: int
+    A length of the feat vector, 0 if the column is not generated.
+:param with_time: bool
+    If the t1 column is generated.
+:param v3_edge: bool
+    If v3_edge_rate is positive. A chosen edge value takes 1 more word to pick it
+    and a NaN v3 skips the edge check.
+:param target_compressibility: bool
+    If target_compressibility is positive. The first row of the batch does not take a draw.
+:param with_string_list: bool
+    If the tags column is generated. The count is for an empty list:
+    the length takes 1 word and each tag takes one more draw.
+
+:return: int
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    nas_enabled,
+    fast_nulls=false,
+    v3_nan=false,
+    id4_id5_corr=false,
+    with_map=false,
+    with_duration=false,
+    monotonic_ts=false,
+    blob_bytes=0,
+    feature_dim=0,
+    with_time=false,
+    v3_edge=false,
+    target_compressibility=false,
+    with_string_list=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn groupby_draws_per_row(
+    nas_enabled: bool,
+    fast_nulls: bool,
+    v3_nan: bool,
+    id4_id5_corr: bool,
+    with_map: bool,
+    with_duration: bool,
+    monotonic_ts: bool,
+    blob_bytes: u64,
+    feature_dim: u64,
+    with_time: bool,
+    v3_edge: bool,
+    target_compressibility: bool,
+    with_string_list: bool,
+) -> u64 {
+    let mut draws = if fast_nulls {
+        // one value draw per id column and one validity draw per id column if NULLs enabled
+        if nas_enabled {
+            12
+        } else {
+            6
+        }
+    } else {
+        12 // NULL check and value per id column
+    };
+    draws += 3; // v1, v2, v3
+    draws += v3_nan as u64;
+    draws += v3_edge as u64;
+    draws += id4_id5_corr as u64;
+    draws += 2 * with_map as u64; // "a" and "b"
+    draws += 2 * with_duration as u64; // NULL check and value
+    draws += monotonic_ts as u64;
+    draws += (blob_bytes > 0) as u64; // NULL check
+    draws += feature_dim;
+    draws += 2 * with_time as u64; // NULL check and value
+    draws += with_string_list as u64; // NULL check
+    draws += target_compressibility as u64;
+    // The blob is filled by 32-bit words and the length of tags is a 32-bit draw
+    2 * draws + blob_bytes.div_ceil(4) + with_string_list as u64
+}
+
+/**
+Generate a contiguous range of rows of the H2O group-by batch without replaying the preceding rows.
+The result is equal to the slice [start_row, start_row + row_count) of generate_groupby
+with the same n, k, nas, seed and batch_size. Without NULLs every row takes the same amount
+of draws (see groupby_draws_per_row), so the generator is fast-forwarded to start_row
+by set_word_pos. NULL cells skip draws, so with positive nas the preceding rows are replayed.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param start_row: int
+    The first row of the range. Should be non-negative.
+:param row_count: int
+    An amount of rows of the range. Should be positive.
+:param batch_size: int
+    A size of the batch the range is taken from. The range should fit into min(n, batch_size).
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn generate_groupby_range(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    start_row: i64,
+    row_count: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let rows = batch_size.min(n);
+    if (start_row < 0) || (row_count <= 0) || (start_row.saturating_add(row_count) > rows) {
+        return Err(PyValueError::new_err(format!(
+            "the range [{}, {} + {}) should be non-empty and fit into {} rows",
+            start_row, start_row, row_count, rows
+        )));
+    }
+    if nas > 0 {
+        let batch = groupby_batch(n, k, nas, seed, start_row + row_count, None, None)?;
+        return Ok(PyArrowType(
+            batch.slice(start_row as usize, row_count as usize),
+        ));
+    }
+
+    let words_per_row = groupby_draws_per_row(
+        false, false, false, false, false, false, false, 0, 0, false, false, false, false,
+    );
+    let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
+    rng.set_word_pos(start_row as u128 * words_per_row as u128);
+    let state = rng_to_state(&rng);
+    let batch = groupby_batch(n, k, nas, seed, row_count, None, Some(&state))?;
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Compute the ground truth selectivity of common filter predicates over the H2O group-by batch.
+The batch is generated exactly like generate_groupby does with the same arguments,
+so the result can be used to tune and validate predicate pushdown benchmarks.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of the batch.
+
+:return: pyarrow.RecordBatch
+    Columns are predicate, matched (the count of matching rows) and selectivity
+    (matched divided by the count of rows). Predicates are id1 IS NULL, id4 IS NULL,
+    v1 = 3, v2 <= 5 and v3 > 50.
+*/
+#[pyfunction]
+fn groupby_predicate_stats(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batch = groupby_batch(n, k, nas, seed, batch_size, None, None)?;
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    let v2 = batch.column(7).as_primitive::<Int64Type>();
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+
+    let predicates = [
+        ("id1 IS NULL", batch.column(0).null_count()),
+        ("id4 IS NULL", batch.column(3).null_count()),
+        ("v1 = 3", v1.values().iter().filter(|v| **v == 3).count()),
+        ("v2 <= 5", v2.values().iter().filter(|v| **v <= 5).count()),
+        ("v3 > 50", v3.values().iter().filter(|v| **v > 50.0).count()),
+    ];
+
+    let mut predicate_builder = StringBuilder::with_capacity(predicates.len(), 64);
+    let mut matched_builder = Int64Builder::with_capacity(predicates.len());
+    let mut selectivity_builder = Float64Builder::with_capacity(predicates.len());
+    for (predicate, matched) in predicates {
+        predicate_builder.append_value(predicate);
+        matched_builder.append_value(matched as i64);
+        selectivity_builder.append_value(matched as f64 / batch.num_rows().max(1) as f64);
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("predicate", DataType::Utf8, false),
+        Field::new("matched", DataType::Int64, false),
+        Field::new("selectivity", DataType::Float64, false),
+    ]);
+
+    let batch = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(predicate_builder.finish()),
+            Arc::new(matched_builder.finish()),
+            Arc::new(selectivity_builder.finish()),
+        ],
+    )
+    .unwrap();
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Compute exact cardinalities of id columns of the H2O group-by batch for sizing hash tables.
+The batch is generated exactly like generate_groupby does with the same arguments.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of the batch.
+
+:return: pyarrow.RecordBatch
+    Columns are column_name and distinct_count (the count of distinct non-null values),
+    one row per id column from id1 to id6.
+*/
+#[pyfunction]
+fn groupby_cardinalities(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batch = groupby_batch(n, k, nas, seed, batch_size, None, None)?;
+
+    let mut name_builder = StringBuilder::with_capacity(6, 18);
+    let mut count_builder = Int64Builder::with_capacity(6);
+    for (i, name) in GROUPBY_COLUMNS[..6].iter().enumerate() {
+        let column = batch.column(i);
+        let distinct = if i < 3 {
+            let values = column.as_string::<i32>();
+            values.iter().flatten().collect::<HashSet<&str>>().len()
+        } else {
+            let values = column.as_primitive::<Int64Type>();
+            values.iter().flatten().collect::<HashSet<i64>>().len()
+        };
+        name_builder.append_value(name);
+        count_builder.append_value(distinct as i64);
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("column_name", DataType::Utf8, false),
+        Field::new("distinct_count", DataType::Int64, false),
+    ]);
+
+    let batch = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(name_builder.finish()),
+            Arc::new(count_builder.finish()),
+        ],
+    )
+    .unwrap();
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Generate a single value column of H2O group-by dataset as a standalone array.
+Values are sampled exactly like generate_groupby does for a column with its own seed,
+so the result is equal to the column of generate_groupby with column_seeds={kind: seed}
+and the same v3_round, without generating the rest of the batch.
+
+:param kind: str
+    A value column to generate: "v1" (1-5, int), "v2" (1-15, int) or "v3" (0-100, float).
+:param rows: int
+    An amount of values. Should be non-negative.
+:param seed: int
+    A random seed value of the column.
+:param v3_round: int | None
+    If passed, v3 values are rounded to this amount of decimal digits. Only for v3.
+
+:return: pyarrow.Array
+*/
+#[pyfunction]
+#[pyo3(signature = (kind, rows, seed, v3_round=None))]
+fn generate_value_column(
+    kind: &str,
+    rows: i64,
+    seed: u64,
+    v3_round: Option<i32>,
+) -> PyResult<PyArrowType<ArrayData>> {
+    if rows < 0 {
+        return Err(PyValueError::new_err(format!(
+            "rows should be non-negative, but got {}",
+            rows
+        )));
+    }
+    if v3_round.is_some() && (kind != "v3") {
+        return Err(PyValueError::new_err(format!(
+            "v3_round is supported only for v3, but got {}",
+            kind
+        )));
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let array: ArrayRef = match kind {
+        "v1" | "v2" => {
+            let distr = Uniform::<i64>::try_from(1..=if kind == "v1" { 5 } else { 15 })
+                .map_err(|e| UniformError(e))?;
+            Arc::new(
+                (0..rows)
+                    .map(|_| distr.sample(&mut rng))
+                    .collect::<Int64Array>(),
+            )
+        }
+        "v3" => {
+            let distr_float = Uniform::<f64>::try_from(0.0..=100.0).map_err(|e| UniformError(e))?;
+            let v3_scale = v3_round.map(|digits| 10f64.powi(digits));
+            Arc::new(
+                (0..rows)
+                    .map(|_| {
+                        let v3 = distr_float.sample(&mut rng);
+                        match v3_scale {
+                            Some(scale) => (v3 * scale).round() / scale,
+                            None => v3,
+                        }
+                    })
+                    .collect::<Float64Array>(),
+            )
+        }
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "kind should be one of \"v1\", \"v2\", \"v3\", but got {}",
+                kind
+            )))
+        }
+    };
+
+    Ok(PyArrowType(array.into_data()))
+}
+
+/**
+Regenerate value columns v1, v2 and v3 for an existing batch, reusing its key columns.
+Values are sampled like generate_groupby does with value_seed=seed, so for a batch of
+generate_groupby the result is equal to generate_groupby with the same value_seed and v3_round.
+Columns of the batch other than v1, v2 and v3 are kept exactly; existing value columns
+are replaced in place and missing ones are appended.
+
+:param key_batch: pyarrow.RecordBatch
+    A batch with key columns, for example a cached output of generate_groupby.
+:param seed: int
+    A random seed value of value columns.
+:param v3_round: int | None
+    If passed, v3 values are rounded to this amount of decimal digits.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+#[pyo3(signature = (key_batch, seed, v3_round=None))]
+fn generate_groupby_values_only(
+    key_batch: PyArrowType<RecordBatch>,
+    seed: u64,
+    v3_round: Option<i32>,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let key_batch = key_batch.0;
+    let rows = key_batch.num_rows();
+    let distr_5 = Uniform::<i64>::try_from(1..=5).map_err(|e| UniformError(e))?;
+    let distr_15 = Uniform::<i64>::try_from(1..=15).map_err(|e| UniformError(e))?;
+    let distr_float = Uniform::<f64>::try_from(0.0..=100.0).map_err(|e| UniformError(e))?;
+    let v3_scale = v3_round.map(|digits| 10f64.powi(digits));
+
+    // v1, v2 and v3 of a row are sampled in order from the same stream
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut v1_builder = Int64Builder::with_capacity(rows);
+    let mut v2_builder = Int64Builder::with_capacity(rows);
+    let mut v3_builder = Float64Builder::with_capacity(rows);
+    for _ in 0..rows {
+        v1_builder.append_value(distr_5.sample(&mut rng));
+        v2_builder.append_value(distr_15.sample(&mut rng));
+        let v3 = distr_float.sample(&mut rng);
+        match v3_scale {
+            Some(scale) => v3_builder.append_value((v3 * scale).round() / scale),
+            None => v3_builder.append_value(v3),
+        }
+    }
+    let values: [(Field, ArrayRef); 3] = [
+        (
+            Field::new("v1", DataType::Int64, false),
+            Arc::new(v1_builder.finish()),
+        ),
+        (
+            Field::new("v2", DataType::Int64, false),
+            Arc::new(v2_builder.finish()),
+        ),
+        (
+            Field::new("v3", DataType::Float64, false),
+            Arc::new(v3_builder.finish()),
+        ),
+    ];
+
+    let mut fields = Vec::with_capacity(key_batch.num_columns() + 3);
+    let mut columns = Vec::with_capacity(key_batch.num_columns() + 3);
+    for (field, column) in key_batch.schema().fields().iter().zip(key_batch.columns()) {
+        match values
+            .iter()
+            .find(|(value, _)| value.name() == field.name())
+        {
+            Some((value, array)) => {
+                fields.push(value.clone());
+                columns.push(array.clone());
+            }
+            None => {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+            }
+        }
+    }
+    for (value, array) in &values {
+        if key_batch.schema().column_with_name(value.name()).is_none() {
+            fields.push(value.clone());
+            columns.push(array.clone());
+        }
+    }
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(ArrowError)?;
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Generate a reproducible permutation of indices for custom shuffling.
+The permutation is a Fisher-Yates shuffle of [0, len) driven by the seeded generator,
+so the result can be passed to pyarrow.compute.take by the caller.
+
+:param len: int
+    An amount of indices. Should be non-negative.
+:param seed: int
+    A random seed value.
+
+:return: pyarrow.Array
+    An Int64 array where every index from [0, len) appears exactly once.
+*/
+#[pyfunction]
+fn deterministic_permutation(len: i64, seed: u64) -> PyResult<PyArrowType<ArrayData>> {
+    if len < 0 {
+        return Err(PyValueError::new_err(format!(
+            "len should be non-negative, but got {}",
+            len
+        )));
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut indices = (0..len).collect::<Vec<i64>>();
+    indices.shuffle(&mut rng);
+
+    Ok(PyArrowType(Int64Array::from(indices).into_data()))
+}
+
+/**
+Compute the expected fraction of NULLs in an id column for the given nas.
+The NULL check keeps the value if a drawn number is >= nas. With exact_percent
+the number is drawn from [0, 100), so the fraction is exactly nas/100.
+The legacy check draws from the inclusive range [0, 100] (101 outcomes),
+so exactly nas outcomes of 101 give NULL. Because of this off-by-one, nas is not
+exactly a percent: nas=50 gives 50/101 (~49.5%) of NULLs and nas=100 still
+leaves 1/101 (~1%) of values non-NULL.
+
+:param nas: int
+    A number from 0 to 100, the nas parameter of generate_groupby.
+:param exact_percent: bool
+    The exact_percent parameter of generate_groupby, true by default.
+
+:return: float
+*/
+#[pyfunction]
+#[pyo3(signature = (nas, exact_percent=true))]
+fn expected_null_fraction(nas: i64, exact_percent: bool) -> PyResult<f64> {
+    if !(0..=100).contains(&nas) {
+        return Err(PyValueError::new_err(format!(
+            "nas should be in [0, 100], but got {}",
+            nas
+        )));
+    }
+    if exact_percent {
+        Ok(nas as f64 / 100.0)
+    } else {
+        Ok(nas as f64 / 101.0)
+    }
+}
+
+/**
+Generate H2O group-by batch where all the id columns are NULL.
+Value columns v1, v2 and v3 are generated as usual.
+It is useful for fuzzing NULL-handling paths of query engines.
+
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of the output batch. Should be positive.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn generate_all_null_groupby(
+    k: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    // nas=100 is an exact percent by default, so all the id columns are NULL
+    let batch = groupby_batch(batch_size.max(k), k, 100, seed, batch_size, None, None)?;
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Preview the first rows of H2O group-by batch without generating the whole batch.
+Rows are generated sequentially from the same random stream, so the preview is
+exactly the first rows of generate_groupby(n, k, nas, seed, batch_size)
+for any batch_size >= rows.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param rows: int
+    An amount of rows to preview. Should be positive and not greater than n.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn preview_groupby(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    rows: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    if (rows <= 0) || (rows > n) {
+        return Err(PyValueError::new_err(format!(
+            "rows should be positive and not greater than {}, but got {}",
+            n, rows
+        )));
+    }
+    let batch = groupby_batch(n, k, nas, seed, rows, None, None)?;
+    Ok(PyArrowType(batch))
+}
+
+/**
+Compute exact quantiles of the v3 column of the H2O group-by batch.
+The batch is generated exactly like generate_groupby does with the same arguments,
+so results can be used to validate approximate quantiles of query engines.
+Quantiles are computed with a linear interpolation between closest ranks.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of the batch.
+:param qs: list[float]
+    Quantiles to compute, each should be from [0, 1].
+
+:return: list[float]
+*/
+#[pyfunction]
+fn groupby_v3_quantiles(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    qs: Vec<f64>,
+) -> PyResult<Vec<f64>> {
+    if let Some(q) = qs.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+        return Err(PyValueError::new_err(format!(
+            "quantiles should be in [0, 1], but got {}",
+            q
+        )));
+    }
+    let batch = groupby_batch(n, k, nas, seed, batch_size, None, None)?;
+    let v3 = batch
+        .column(batch.schema().index_of("v3").map_err(ArrowError)?)
+        .as_primitive::<Float64Type>();
+    let mut values = v3.values().to_vec();
+    if values.is_empty() {
+        return Err(PyValueError::new_err(
+            "cannot compute quantiles of an empty batch",
+        ));
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let last = (values.len() - 1) as f64;
+    Ok(qs
+        .iter()
+        .map(|q| {
+            let rank = q * last;
+            let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+            values[lower] + (values[upper] - values[lower]) * (rank - rank.floor())
+        })
+        .collect())
+}
+
+/**
+Compute the exact ground truth of the aggregation by id1 of the H2O group-by batch.
+The batch is generated exactly like generate_groupby does with the same arguments,
+so the result can be used as a reference for sum / mean aggregations of query engines.
+Rows with NULL id1 are aggregated into a separate group with NULL key.
+
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of the batch.
+
+:return: pyarrow.RecordBatch
+    Columns are id1, count, v1_sum, v2_sum and v3_mean, sorted by id1.
+*/
+#[pyfunction]
+fn groupby_ground_truth(
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batch = groupby_batch(n, k, nas, seed, batch_size, None, None)?;
+    let id1 = batch.column(0).as_string::<i32>();
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    let v2 = batch.column(7).as_primitive::<Int64Type>();
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+
+    // (count, sum(v1), sum(v2), sum(v3)) per group
+    let mut groups = BTreeMap::<Option<&str>, (i64, i64, i64, f64)>::new();
+    for i in 0..batch.num_rows() {
+        let key = (!id1.is_null(i)).then(|| id1.value(i));
+        let group = groups.entry(key).or_insert((0, 0, 0, 0.0));
+        group.0 += 1;
+        group.1 += v1.value(i);
+        group.2 += v2.value(i);
+        group.3 += v3.value(i);
+    }
+
+    let mut id1_builder = StringBuilder::with_capacity(groups.len(), groups.len() * 5);
+    let mut count_builder = Int64Builder::with_capacity(groups.len());
+    let mut v1_builder = Int64Builder::with_capacity(groups.len());
+    let mut v2_builder = Int64Builder::with_capacity(groups.len());
+    let mut v3_builder = Float64Builder::with_capacity(groups.len());
+    for (key, (count, v1_sum, v2_sum, v3_sum)) in groups {
+        id1_builder.append_option(key);
+        count_builder.append_value(count);
+        v1_builder.append_value(v1_sum);
+        v2_builder.append_value(v2_sum);
+        v3_builder.append_value(v3_sum / count as f64);
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("id1", DataType::Utf8, true),
+        Field::new("count", DataType::Int64, false),
+        Field::new("v1_sum", DataType::Int64, false),
+        Field::new("v2_sum", DataType::Int64, false),
+        Field::new("v3_mean", DataType::Float64, false),
+    ]);
+
+    let batch = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(id1_builder.finish()),
+            Arc::new(count_builder.finish()),
+            Arc::new(v1_builder.finish()),
+            Arc::new(v2_builder.finish()),
+            Arc::new(v3_builder.finish()),
+        ],
+    )
+    .unwrap();
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Generate H2O group-by batch concatenated from independently generated segments.
+Each segment is a dict of keyword arguments of generate_groupby, for example
+[{"n": 100, "k": 10, "nas": 0, "seed": 1, "batch_size": 50},
+ {"n": 100, "k": 10, "nas": 0, "seed": 2, "batch_size": 50, "key_dist": "pareto"}]
+gives a batch where the first half has uniform keys and the second half has long-tailed keys.
+All the segments should produce the same schema.
+
+:param segments: list[dict]
+    Keyword arguments of generate_groupby per segment. Should be non-empty.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn generate_groupby_segments(
+    py: Python<'_>,
+    segments: Vec<Bound<'_, PyDict>>,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    if segments.is_empty() {
+        return Err(PyValueError::new_err("segments should be non-empty"));
+    }
+    let generate = wrap_pyfunction!(generate_groupby, py)?;
+    let mut batches = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let batch: PyArrowType<RecordBatch> = generate.call((), Some(&segment))?.extract()?;
+        batches.push(batch.0);
+    }
+
+    Ok(PyArrowType(concat_segments(&batches)?))
+}
+
+/// Concatenate batches of segments of generate_groupby_segments, which should share the schema.
+fn concat_segments(batches: &[RecordBatch]) -> PyResult<RecordBatch> {
+    let schema = batches[0].schema();
+    if let Some(i) = batches.iter().position(|batch| batch.schema() != schema) {
+        return Err(PyValueError::new_err(format!(
+            "all the segments should have the same schema, but segment {} differs from segment 0",
+            i
+        )));
+    }
+    Ok(concat_batches(&schema, batches).map_err(ArrowError)?)
+}
+
+/// A stable FNV-1a hash, used to route rows to buckets independently of the Rust version.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ (*byte as u64)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Hash id1, ..., id6, v1, v2, v3 of every row with FNV-1a, see with_checksum of generate_groupby.
+fn row_checksums(batch: &RecordBatch) -> Result<Int64Array, arrow::error::ArrowError> {
+    let mut rows = vec![Vec::<u8>::new(); batch.num_rows()];
+    for name in GROUPBY_COLUMNS {
+        let column = batch
+            .column_by_name(name)
+            .expect("Internal error: base column is missing");
+        let column = match column.data_type() {
+            DataType::Utf8 | DataType::Binary | DataType::Int64 | DataType::Float64 => {
+                column.clone()
+            }
+            _ => cast(column, &DataType::Float64)?,
+        };
+        for (i, row) in rows.iter_mut().enumerate() {
+            if column.is_null(i) {
+                row.push(0);
+                continue;
+            }
+            row.push(1);
+            match column.data_type() {
+                DataType::Utf8 | DataType::Binary => {
+                    let value = match column.as_string_opt::<i32>() {
+                        Some(strings) => strings.value(i).as_bytes(),
+                        None => column.as_binary::<i32>().value(i),
+                    };
+                    row.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    row.extend_from_slice(value);
+                }
+                DataType::Int64 => {
+                    let value = column.as_primitive::<Int64Type>().value(i);
+                    row.extend_from_slice(&value.to_le_bytes());
+                }
+                _ => {
+                    let value = column.as_primitive::<Float64Type>().value(i);
+                    row.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+    }
+    Ok(rows.iter().map(|row| fnv1a(row) as i64).collect())
+}
+
+/**
+Generate H2O group-by dataset into a directory of parquet files partitioned by id1.
+Each row goes to the file part-{bucket}.parquet, where bucket is hash(id1) % buckets.
+Rows with NULL id1 go to the dedicated file part-null.parquet.
+
+:param dir: str
+    An output directory. Created if not exists.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value. Seeds of batches are derived from it.
+:param batch_size: int
+    A size of each generated batch. Should be positive.
+:param buckets: int
+    An amount of hash buckets. Should be positive.
+
+:return: list[int]
+    Row counts per bucket; the last element is the count of the NULL bucket.
+*/
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_to_parquet_partitioned(
+    dir: PathBuf,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    buckets: i64,
+) -> PyResult<Vec<i64>> {
+    if buckets <= 0 {
+        return Err(PyValueError::new_err(format!(
+            "buckets should be positive, but got {}",
+            buckets
+        )));
+    }
+    let reader = GroupByReader::try_new(n, k, nas, seed, batch_size, None, true, false)?;
+    let schema = reader.schema();
+    std::fs::create_dir_all(&dir)?;
+
+    let num_buckets = buckets as usize;
+    let mut writers = Vec::with_capacity(num_buckets + 1);
+    for bucket in 0..=num_buckets {
+        let file_name = if bucket == num_buckets {
+            "part-null.parquet".to_string()
+        } else {
+            format!("part-{:05}.parquet", bucket)
+        };
+        let file = File::create(dir.join(file_name))?;
+        writers.push(ArrowWriter::try_new(file, schema.clone(), None).map_err(ParquetError)?);
+    }
+    let mut counts = vec![0i64; num_buckets + 1];
+
+    for batch in reader {
+        let batch = batch.map_err(ArrowError)?;
+        let id1 = batch.column(0).as_string::<i32>();
+        let mut indices = vec![Vec::<u32>::new(); num_buckets + 1];
+        for i in 0..batch.num_rows() {
+            let bucket = if id1.is_null(i) {
+                num_buckets
+            } else {
+                (fnv1a(id1.value(i).as_bytes()) % (num_buckets as u64)) as usize
+            };
+            indices[bucket].push(i as u32);
+        }
+        for (bucket, bucket_indices) in indices.into_iter().enumerate() {
+            if bucket_indices.is_empty() {
+                continue;
+            }
+            counts[bucket] += bucket_indices.len() as i64;
+            let part = take_record_batch(&batch, &UInt32Array::from(bucket_indices))
+                .map_err(ArrowError)?;
+            writers[bucket].write(&part).map_err(ParquetError)?;
+        }
+    }
+
+    for writer in writers {
+        writer.close().map_err(ParquetError)?;
+    }
+
+    Ok(counts)
+}
+
+/// Write the sidecar <path>.meta.json of a written data file with its schema, the amount
+/// of rows, parameters of the generation and NULL counts per column.
+fn write_sidecar_file(
+    path: &Path,
+    schema: &Schema,
+    rows: i64,
+    seed: i64,
+    params: serde_json::Value,
+    null_counts: &[usize],
+) -> PyResult<()> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            json!({
+                "name": field.name(),
+                "type": field.data_type().to_string(),
+                "nullable": field.is_nullable(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let null_counts = schema
+        .fields()
+        .iter()
+        .zip(null_counts)
+        .map(|(field, count)| (field.name().clone(), json!(count)))
+        .collect::<serde_json::Map<_, _>>();
+    let meta = json!({
+        "schema": fields,
+        "rows": rows,
+        "seed": seed,
+        "params": params,
+        "null_counts": null_counts,
+    });
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".meta.json");
+    std::fs::write(sidecar, meta.to_string())?;
+    Ok(())
+}
+
+/// Parse the parquet codec and validate its level: [0, 10] for gzip and [1, 22] for zstd.
+fn parquet_compression(codec: &str, level: Option<i32>) -> PyResult<Compression> {
+    match (codec, level) {
+        ("uncompressed", None) => Ok(Compression::UNCOMPRESSED),
+        ("snappy", None) => Ok(Compression::SNAPPY),
+        ("gzip", None) => Ok(Compression::GZIP(GzipLevel::default())),
+        ("gzip", Some(level)) => u32::try_from(level)
+            .ok()
+            .and_then(|level| GzipLevel::try_new(level).ok())
+            .map(Compression::GZIP)
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "compression_level should be in [0, 10] for gzip, but got {}",
+                    level
+                ))
+            }),
+        #[cfg(feature = "zstd")]
+        ("zstd", None) => Ok(Compression::ZSTD(ZstdLevel::default())),
+        #[cfg(feature = "zstd")]
+        ("zstd", Some(level)) => ZstdLevel::try_new(level)
+            .map(Compression::ZSTD)
+            .map_err(|_| {
+                PyValueError::new_err(format!(
+                    "compression_level should be in [1, 22] for zstd, but got {}",
+                    level
+                ))
+            }),
+        #[cfg(not(feature = "zstd"))]
+        ("zstd", _) => Err(PyValueError::new_err(
+            "zstd compression requires falsa built with the zstd feature",
+        )),
+        ("uncompressed" | "snappy", Some(level)) => Err(PyValueError::new_err(format!(
+            "{} does not support compression_level, but got {}",
+            codec, level
+        ))),
+        _ => Err(PyValueError::new_err(format!(
+            "compression should be one of uncompressed, snappy, gzip, zstd, but got {}",
+            codec
+        ))),
+    }
+}
+
+/**
+Generate H2O group-by dataset into a single parquet file using multiple threads.
+Batches are generated in parallel by groups of `threads` batches and written
+sequentially in the order of batches, one row group per batch, so the content
+of the file does not depend on the amount of threads.
+Seeds of batches are derived from the seed like in generate_groupby_reader with batch_salt.
+
+:param path: str
+    A path to the output parquet file. Overwritten if exists.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch (row group). Should be positive.
+:param threads: int
+    An amount of threads generating batches. Should be positive, 1 by default.
+:param compression: str
+    A compression codec: "uncompressed" (default), "snappy", "gzip" or "zstd".
+    zstd requires the zstd cargo feature, which is enabled by default.
+:param compression_level: int | None
+    A level of the codec, from [0, 10] for gzip and from [1, 22] for zstd.
+    Other codecs do not support levels. By default the codec's default level is used.
+:param rowgroup_sorted: bool
+    If true, each batch is sorted by id1 before it is written as a row group:
+    row groups are internally sorted, but the file is not globally sorted.
+:param write_sidecar: bool
+    If true, <path>.meta.json is written next to the file with the schema, the amount
+    of rows, the seed, parameters and NULL counts per column.
+
+:return: int
+    An amount of written rows.
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    n,
+    k,
+    nas,
+    seed,
+    batch_size,
+    threads=1,
+    compression="uncompressed",
+    compression_level=None,
+    rowgroup_sorted=false,
+    write_sidecar=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_to_parquet(
+    path: PathBuf,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    threads: usize,
+    compression: &str,
+    compression_level: Option<i32>,
+    rowgroup_sorted: bool,
+    write_sidecar: bool,
+) -> PyResult<i64> {
+    if threads == 0 {
+        return Err(PyValueError::new_err("threads should be positive"));
+    }
+    let props = WriterProperties::builder()
+        .set_compression(parquet_compression(compression, compression_level)?)
+        .build();
+    let batches = split_batches(n, seed, batch_size, true)?;
+    let mut writer: Option<ArrowWriter<File>> = None;
+    let mut schema: Option<SchemaRef> = None;
+    let mut total_rows = 0i64;
+    let mut null_counts = Vec::new();
+
+    for chunk in batches.chunks(threads) {
+        let generated = std::thread::scope(|scope| {
+            let handles = chunk
+                .iter()
+                .map(|&(batch_seed, size)| {
+                    scope.spawn(move || {
+                        let batch = groupby_batch(n, k, nas, batch_seed, size, None, None)?;
+                        if rowgroup_sorted {
+                            Ok(sort_by_keys(&batch, &["id1"]).map_err(ArrowError)?)
+                        } else {
+                            Ok(batch)
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .expect("Internal error: generation thread panicked")
+                })
+                .collect::<Vec<PyResult<RecordBatch>>>()
+        });
+        for batch in generated {
+            let batch = batch?;
+            if writer.is_none() {
+                let file = File::create(&path)?;
+                writer = Some(
+                    ArrowWriter::try_new(file, batch.schema(), Some(props.clone()))
+                        .map_err(ParquetError)?,
+                );
+                schema = Some(batch.schema());
+                null_counts = vec![0; batch.num_columns()];
+            }
+            let writer = writer.as_mut().unwrap();
+            writer.write(&batch).map_err(ParquetError)?;
+            // Close the row group, so each batch is a separate row group
+            writer.flush().map_err(ParquetError)?;
+            total_rows += batch.num_rows() as i64;
+            for (count, column) in null_counts.iter_mut().zip(batch.columns()) {
+                *count += column.null_count();
+            }
+        }
+    }
+    if let Some(writer) = writer {
+        writer.close().map_err(ParquetError)?;
+    }
+    if let Some(schema) = schema.filter(|_| write_sidecar) {
+        let params = json!({
+            "n": n,
+            "k": k,
+            "nas": nas,
+            "batch_size": batch_size,
+            "compression": compression,
+            "compression_level": compression_level,
+            "rowgroup_sorted": rowgroup_sorted,
+        });
+        write_sidecar_file(&path, &schema, total_rows, seed, params, &null_counts)?;
+    }
+
+    Ok(total_rows)
+}
+
+/**
+Generate H2O group-by dataset into a parquet object in an object store without local disk.
+Batches are streamed to the store by a multipart upload, one row group per batch.
+Credentials and other options of the store are read from environment variables,
+for example AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and AWS_REGION for S3
+or GOOGLE_SERVICE_ACCOUNT for GCS. Other variables are ignored.
+Seeds of batches are derived from the seed like in generate_groupby_reader with batch_salt.
+
+:param url: str
+    A URL of the output object, for example "s3://bucket/groupby.parquet" or
+    "gs://bucket/groupby.parquet". Overwritten if exists. "memory:///groupby.parquet"
+    writes to an in-memory store that is dropped after the call.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch (row group). Should be positive.
+
+:return: int
+    An amount of written rows.
+*/
+#[pyfunction]
+fn generate_groupby_to_object_store(
+    url: &str,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<i64> {
+    let url = Url::parse(url)
+        .map_err(|e| PyValueError::new_err(format!("url {} is not valid: {}", url, e)))?;
+    // Store options are lowercase, like in AmazonS3Builder::from_env
+    let options = std::env::vars()
+        .filter(|(key, _)| key.starts_with("AWS_") || key.starts_with("GOOGLE_"))
+        .map(|(key, value)| (key.to_ascii_lowercase(), value));
+    let (store, path) = object_store::parse_url_opts(&url, options).map_err(ObjectStoreError)?;
+    write_groupby_to_store(Arc::from(store), path, n, k, nas, seed, batch_size)
+}
+
+/// Stream parquet batches of generate_groupby_to_object_store into the object of the store.
+fn write_groupby_to_store(
+    store: Arc<dyn ObjectStore>,
+    path: object_store::path::Path,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<i64> {
+    let reader = GroupByReader::try_new(n, k, nas, seed, batch_size, None, true, false)?;
+    let schema = reader.schema();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let object = ParquetObjectWriter::new(store, path);
+        let mut writer = AsyncArrowWriter::try_new(object, schema, None).map_err(ParquetError)?;
+        let mut total_rows = 0i64;
+        for batch in reader {
+            let batch = batch.map_err(ArrowError)?;
+            writer.write(&batch).await.map_err(ParquetError)?;
+            // Close the row group, so each batch is a separate row group
+            writer.flush().await.map_err(ParquetError)?;
+            total_rows += batch.num_rows() as i64;
+        }
+        writer.close().await.map_err(ParquetError)?;
+        Ok::<i64, PyErr>(total_rows)
+    })
+}
+
+/**
+Generate H2O group-by dataset into a single Arrow IPC file.
+Batches are buffered by the writer and the underlying file is flushed
+after every flush_every batches: rare flushes give a better throughput,
+frequent flushes make written data visible to readers earlier.
+Seeds of batches are derived from the seed like in generate_groupby_reader with batch_salt.
+
+:param path: str
+    A path to the output IPC file. Overwritten if exists.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch. Should be positive.
+:param flush_every: int
+    An amount of batches written between flushes. Should be positive, 1 by default.
+:param alignment: int
+    An alignment of buffers in bytes: 8, 16, 32 or 64 (default).
+:param metadata_version: str
+    A version of IPC metadata: "v4" or "v5" (default).
+:param continuation_marker: bool
+    If false, messages are written in the legacy format without the continuation
+    marker, which requires metadata_version "v4". True by default.
+:param write_sidecar: bool
+    If true, <path>.meta.json is written next to the file with the schema, the amount
+    of rows, the seed, parameters and NULL counts per column.
+
+:return: int
+    An amount of written rows.
+*/
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    n,
+    k,
+    nas,
+    seed,
+    batch_size,
+    flush_every=1,
+    alignment=64,
+    metadata_version="v5",
+    continuation_marker=true,
+    write_sidecar=false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_groupby_to_ipc(
+    path: PathBuf,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+    flush_every: i64,
+    alignment: usize,
+    metadata_version: &str,
+    continuation_marker: bool,
+    write_sidecar: bool,
+) -> PyResult<i64> {
+    if flush_every <= 0 {
+        return Err(PyValueError::new_err(format!(
+            "flush_every should be positive, but got {}",
+            flush_every
+        )));
+    }
+    let version = match metadata_version {
+        "v4" => MetadataVersion::V4,
+        "v5" => MetadataVersion::V5,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "metadata_version should be one of \"v4\", \"v5\", but got {}",
+                metadata_version
+            )))
+        }
+    };
+    // Checks the alignment and that the legacy format is used only with metadata v4
+    let options =
+        IpcWriteOptions::try_new(alignment, !continuation_marker, version).map_err(ArrowError)?;
+    let reader = GroupByReader::try_new(n, k, nas, seed, batch_size, None, true, false)?;
+    let schema = reader.schema();
+    let file = BufWriter::new(File::create(&path)?);
+    let mut writer =
+        FileWriter::try_new_with_options(file, &schema, options).map_err(ArrowError)?;
+    let mut total_rows = 0i64;
+    let mut null_counts = vec![0; schema.fields().len()];
+
+    for (i, batch) in reader.enumerate() {
+        let batch = batch.map_err(ArrowError)?;
+        writer.write(&batch).map_err(ArrowError)?;
+        total_rows += batch.num_rows() as i64;
+        for (count, column) in null_counts.iter_mut().zip(batch.columns()) {
+            *count += column.null_count();
+        }
+        if (i as i64 + 1) % flush_every == 0 {
+            writer.flush().map_err(ArrowError)?;
+        }
+    }
+    writer.finish().map_err(ArrowError)?;
+    if write_sidecar {
+        let params = json!({
+            "n": n,
+            "k": k,
+            "nas": nas,
+            "batch_size": batch_size,
+            "alignment": alignment,
+            "metadata_version": metadata_version,
+            "continuation_marker": continuation_marker,
+        });
+        write_sidecar_file(&path, &schema, total_rows, seed, params, &null_counts)?;
+    }
+
+    Ok(total_rows)
+}
+
+/**
+Generate H2O group-by dataset into a shared memory segment in the Arrow IPC file format.
+It allows to hand data over to a co-located process without disk: the reader opens
+the segment by name (shm_open + mmap on Linux and macOS, a named file mapping
+on Windows) and reads it as an IPC file. The segment outlives this call, the reader
+is responsible to unlink it when data are consumed.
+Seeds of batches are derived from the seed like in generate_groupby_reader with batch_salt.
+
+:param name: str
+    A name of the shared memory segment, for example "/falsa-groupby". Should not exist.
+:param n: int
+    A total amount of rows in dataset. Should be positive.
+:param k: int
+    An amount of grouping keys. Should be positive.
+:param nas: int
+    A number from 0 to 100 that represent a percent of NULLs.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch. Should be positive.
+
+:return: tuple[str, int]
+    The name of the segment and its size in bytes.
+*/
+#[pyfunction]
+fn generate_groupby_to_shm(
+    name: &str,
+    n: i64,
+    k: i64,
+    nas: i64,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<(String, usize)> {
+    let reader = GroupByReader::try_new(n, k, nas, seed, batch_size, None, true, false)?;
+    let schema = reader.schema();
+    let mut writer = FileWriter::try_new(Vec::new(), &schema).map_err(ArrowError)?;
+    for batch in reader {
+        writer
+            .write(&batch.map_err(ArrowError)?)
+            .map_err(ArrowError)?;
+    }
+    writer.finish().map_err(ArrowError)?;
+    let bytes = writer.into_inner().map_err(ArrowError)?;
+
+    let mut shmem = ShmemConf::new()
+        .size(bytes.len())
+        .os_id(name)
+        .create()
+        .map_err(|e| {
+            PyIOError::new_err(format!("failed to create shared memory {}: {}", name, e))
+        })?;
+    // The segment is created exclusively, so nobody else writes into it yet
+    unsafe { shmem.as_slice_mut() }.copy_from_slice(&bytes);
+    // Keep the segment after the mapping is dropped, it belongs to the reader now
+    shmem.set_owner(false);
+
+    Ok((shmem.get_os_id().to_string(), bytes.len()))
+}
+
 /**
-Generate H2O group-by dataset.
-Running this function multiple time with the same seed
-will constantly return exactly the same batch!
+Append H2O group-by dataset to the parquet file.
+Parquet does not support appending to a finished file, so if the file exists,
+it is rewritten: existing row groups are copied to a temporary file first,
+new batches are written after them and the temporary file replaces the original one.
+On errors the temporary file is removed and the original one is left as is.
+The schema of the existing file should match the schema of generated batches.
 
+:param path: str
+    A path to the parquet file. Created if not exists.
 :param n: int
-    A total amount of rows in dataset. Should be positive.
-    Passing a negative value or zero may tend to runtime errors / panic.
+    An amount of rows to append. Should be positive.
 :param k: int
     An amount of grouping keys. Should be positive.
-    Passing a negative value or zero may tend to runtime errors / panic.
 :param nas: int
-    A number from 1 to 100 that represent a percent of NULLs.
-    Passing a value not from [0-100] may tend to unpredictable behavior.
+    A number from 0 to 100 that represent a percent of NULLs.
 :param seed: int
-    A random seed value. Should be positive!
-    Passing a negative value may tend to unpredictable behavior.
+    A random seed value. Seeds of batches are derived from it.
 :param batch_size: int
-    A size of the output batch.
+    A size of each generated batch. Should be positive.
 
-:return: pyarrow.RecordBatch
+:return: int
+    A total amount of rows in the file after appending.
 */
 #[pyfunction]
-fn generate_groupby(
+fn append_groupby_to_parquet(
+    path: PathBuf,
     n: i64,
     k: i64,
     nas: i64,
     seed: i64,
     batch_size: i64,
-) -> PyResult<PyArrowType<RecordBatch>> {
-    let distr_k = Uniform::<i64>::try_from(1..=k).map_err(|e| UniformError(e))?;
-    let distr_nk = Uniform::<i64>::try_from(1..=(n / k)).map_err(|e| UniformError(e))?;
-    let distr_5 = Uniform::<i64>::try_from(1..=5).map_err(|e| UniformError(e))?;
-    let distr_15 = Uniform::<i64>::try_from(1..=15).map_err(|e| UniformError(e))?;
-    let distr_float = Uniform::<f64>::try_from(0.0..=100.0).map_err(|e| UniformError(e))?;
-    let distr_nas = Uniform::<i64>::try_from(0..=100).map_err(|e| UniformError(e))?;
-    let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
-
-    let item_capacity = batch_size as usize; // validataion is on the python side
+) -> PyResult<i64> {
+    let reader = GroupByReader::try_new(n, k, nas, seed, batch_size, None, true, false)?;
+    let schema = reader.schema();
 
-    let mut id1_builder = StringBuilder::with_capacity(item_capacity, item_capacity * 8 * 5); // id{:03}, utf8
-    let mut id2_builder = StringBuilder::with_capacity(item_capacity, item_capacity * 8 * 5); // id{:03}, utf8
-    let mut id3_builder = StringBuilder::with_capacity(item_capacity, item_capacity * 8 * 12); // id{:010}, utf8
-    let mut id4_builder = Int64Builder::with_capacity(item_capacity);
-    let mut id5_builder = Int64Builder::with_capacity(item_capacity);
-    let mut id6_builder = Int64Builder::with_capacity(item_capacity);
-    let mut v1_builder = Int64Builder::with_capacity(item_capacity);
-    let mut v2_builder = Int64Builder::with_capacity(item_capacity);
-    let mut v3_builder = Float64Builder::with_capacity(item_capacity);
+    let mut tmp_path = path.clone().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let write_tmp = || -> PyResult<i64> {
+        let mut writer = ArrowWriter::try_new(File::create(&tmp_path)?, schema.clone(), None)
+            .map_err(ParquetError)?;
+        let mut total_rows = 0i64;
 
-    for _i in 0..batch_size {
-        // id1, string in form id123, 123 from 1-K
-        if distr_nas.sample(&mut rng) >= nas {
-            id1_builder.append_value(format!("id{:03}", distr_k.sample(&mut rng)))
-        } else {
-            id1_builder.append_null()
-        }
-        // id2, string in form id123, 123 from 1-K
-        if distr_nas.sample(&mut rng) >= nas {
-            id2_builder.append_value(format!("id{:03}", distr_nk.sample(&mut rng)))
-        } else {
-            id2_builder.append_null()
-        }
-        // id3, string in form id1234567890, number from 1-N/K
-        if distr_nas.sample(&mut rng) >= nas {
-            id3_builder.append_value(format!("id{:010}", distr_nk.sample(&mut rng)))
-        } else {
-            id3_builder.append_null()
+        if path.exists() {
+            let existing = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)
+                .map_err(ParquetError)?;
+            if existing.schema().fields() != schema.fields() {
+                return Err(PyValueError::new_err(format!(
+                    "schema of the existing file does not match the generated one: {:?} != {:?}",
+                    existing.schema().fields(),
+                    schema.fields()
+                )));
+            }
+            for batch in existing.build().map_err(ParquetError)? {
+                let batch = batch.map_err(ArrowError)?;
+                total_rows += batch.num_rows() as i64;
+                writer.write(&batch).map_err(ParquetError)?;
+            }
         }
-        // id4, 1-K, int
-        if distr_nas.sample(&mut rng) >= nas {
-            id4_builder.append_value(distr_k.sample(&mut rng))
-        } else {
-            id4_builder.append_null()
+
+        for batch in reader {
+            let batch = batch.map_err(ArrowError)?;
+            total_rows += batch.num_rows() as i64;
+            writer.write(&batch).map_err(ParquetError)?;
         }
-        // id5, 1-K, int
-        if distr_nas.sample(&mut rng) >= nas {
-            id5_builder.append_value(distr_k.sample(&mut rng))
-        } else {
-            id5_builder.append_null()
+        writer.close().map_err(ParquetError)?;
+        Ok(total_rows)
+    };
+
+    match write_tmp() {
+        Ok(total_rows) => {
+            std::fs::rename(&tmp_path, &path)?;
+            Ok(total_rows)
         }
-        // id6, 1-N/K, int
-        if distr_nas.sample(&mut rng) >= nas {
-            id6_builder.append_value(distr_nk.sample(&mut rng))
-        } else {
-            id6_builder.append_null()
+        Err(e) => {
+            // The original file is untouched, only the partial temporary one is dropped
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(e)
         }
-        // v1, 1-5, int
-        v1_builder.append_value(distr_5.sample(&mut rng));
-        // v2, 1-15, int
-        v2_builder.append_value(distr_15.sample(&mut rng));
-        // v3, random float
-        v3_builder.append_value(distr_float.sample(&mut rng));
     }
-
-    let schema = Schema::new(vec![
-        Field::new("id1", DataType::Utf8, true),
-        Field::new("id2", DataType::Utf8, true),
-        Field::new("id3", DataType::Utf8, true),
-        Field::new("id4", DataType::Int64, true),
-        Field::new("id5", DataType::Int64, true),
-        Field::new("id6", DataType::Int64, true),
-        Field::new("v1", DataType::Int64, false),
-        Field::new("v2", DataType::Int64, false),
-        Field::new("v3", DataType::Float64, false),
-    ]);
-
-    let batch = RecordBatch::try_new(
-        Arc::new(schema),
-        vec![
-            Arc::new(id1_builder.finish()),
-            Arc::new(id2_builder.finish()),
-            Arc::new(id3_builder.finish()),
-            Arc::new(id4_builder.finish()),
-            Arc::new(id5_builder.finish()),
-            Arc::new(id6_builder.finish()),
-            Arc::new(v1_builder.finish()),
-            Arc::new(v2_builder.finish()),
-            Arc::new(v3_builder.finish()),
-        ],
-    )
-    .unwrap();
-
-    Ok(PyArrowType(batch))
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    n,
+    seed,
+    k1,
+    k2,
+    k3,
+    batch_size,
+    sort_by_key=false,
+    dict_keys=false,
+    int_measure=false,
+    measure_min=1.0,
+    measure_max=100.0,
+    measure_dist="uniform",
+))]
+#[allow(clippy::too_many_arguments)]
 fn generate_join_lhs(
     n: i64,
     seed: i64,
@@ -161,13 +4286,29 @@ fn generate_join_lhs(
     k2: PyArrowType<ArrayData>,
     k3: PyArrowType<ArrayData>,
     batch_size: i64,
+    sort_by_key: bool,
+    dict_keys: bool,
+    int_measure: bool,
+    measure_min: f64,
+    measure_max: f64,
+    measure_dist: &str,
 ) -> PyResult<PyArrowType<RecordBatch>> {
     let k1_array = Int64Array::try_from(k1.0)?;
     let k2_array = Int64Array::try_from(k2.0)?;
     let k3_array = Int64Array::try_from(k3.0)?;
+    validate_keys("k1", &k1_array)?;
+    validate_keys("k2", &k2_array)?;
+    validate_keys("k3", &k3_array)?;
     let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
-    let distr_float = Uniform::<f64>::try_from(1.0..=100.0).map_err(|e| UniformError(e))?;
     let item_capacity = batch_size as usize; // validation is on the python side
+    let v1_array = join_measure(
+        int_measure,
+        measure_min,
+        measure_max,
+        measure_dist,
+        item_capacity,
+        &mut rng,
+    )?;
 
     assert!(
         k1_array.len() == item_capacity,
@@ -195,26 +4336,30 @@ fn generate_join_lhs(
         Field::new("id4", DataType::Utf8, false),
         Field::new("id5", DataType::Utf8, false),
         Field::new("id6", DataType::Utf8, false),
-        Field::new("v1", DataType::Float64, false),
+        Field::new("v1", v1_array.data_type().clone(), false),
     ]);
 
     let len_of_max_key = (n * 11 / 10 / 1_000_000).to_string().len() + 2; // id{}, where {} is a number from a vec
-    let mut id4_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut id5_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut id6_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut v1_builder = Float64Builder::with_capacity(item_capacity);
+    let mut id4_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
+    let mut id5_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
+    let mut id6_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
 
     for _i in 0..item_capacity {
         id4_builder.append_value(format! {"id{}", k1_array.value(_i)});
         id5_builder.append_value(format! {"id{}", k2_array.value(_i)});
         id6_builder.append_value(format! {"id{}", k3_array.value(_i)});
-        v1_builder.append_value(distr_float.sample(&mut rng));
     }
 
-    let batch = RecordBatch::try_new(
+    let mut batch = RecordBatch::try_new(
         Arc::new(schema),
         vec![
             Arc::new(k1_array),
@@ -223,25 +4368,68 @@ fn generate_join_lhs(
             Arc::new(id4_builder.finish()),
             Arc::new(id5_builder.finish()),
             Arc::new(id6_builder.finish()),
-            Arc::new(v1_builder.finish()),
+            v1_array,
         ],
     )
     .unwrap();
 
+    if sort_by_key {
+        batch = sort_by_keys(&batch, &["id1", "id2", "id3"]).map_err(ArrowError)?;
+    }
+
+    if dict_keys {
+        batch = dictionary_encode_keys(&batch).map_err(ArrowError)?;
+    }
+
     Ok(PyArrowType(batch))
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    n,
+    seed,
+    k1,
+    batch_size,
+    sort_by_key=false,
+    dict_keys=false,
+    int_measure=false,
+    disjoint=false,
+    measure_min=1.0,
+    measure_max=100.0,
+    measure_dist="uniform",
+))]
+#[allow(clippy::too_many_arguments)]
 fn generate_join_rhs_small(
     n: i64,
     seed: i64,
     k1: PyArrowType<ArrayData>,
     batch_size: i64,
+    sort_by_key: bool,
+    dict_keys: bool,
+    int_measure: bool,
+    disjoint: bool,
+    measure_min: f64,
+    measure_max: f64,
+    measure_dist: &str,
 ) -> PyResult<PyArrowType<RecordBatch>> {
     let k1_array = Int64Array::try_from(k1.0)?;
+    validate_keys("k1", &k1_array)?;
+    // Keys are shifted out of the lhs key space for anti-join benchmarks
+    let k1_array = if disjoint {
+        disjoint_keys(k1_array, join_key_levels(n / 1_000_000))
+    } else {
+        k1_array
+    };
     let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
-    let distr_float = Uniform::<f64>::try_from(1.0..=100.0).map_err(|e| UniformError(e))?;
     let item_capacity = batch_size as usize; // validation is on the python side
+    let v2_array = join_measure(
+        int_measure,
+        measure_min,
+        measure_max,
+        measure_dist,
+        item_capacity,
+        &mut rng,
+    )?;
 
     assert!(
         k1_array.len() == item_capacity,
@@ -253,45 +4441,89 @@ fn generate_join_rhs_small(
     let schema = Schema::new(vec![
         Field::new("id1", DataType::Int64, false),
         Field::new("id4", DataType::Utf8, false),
-        Field::new("v2", DataType::Float64, false),
+        Field::new("v2", v2_array.data_type().clone(), false),
     ]);
 
     let len_of_max_key = (n * 11 / 10 / 1_000_000).to_string().len() + 2; // id{}, where {} is a number from a vec
-    let mut id4_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut v2_builder = Float64Builder::with_capacity(item_capacity);
+    let mut id4_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
 
     for _i in 0..item_capacity {
         id4_builder.append_value(format! {"id{}", k1_array.value(_i)});
-        v2_builder.append_value(distr_float.sample(&mut rng));
     }
 
-    let batch = RecordBatch::try_new(
+    let mut batch = RecordBatch::try_new(
         Arc::new(schema),
-        vec![
-            Arc::new(k1_array),
-            Arc::new(id4_builder.finish()),
-            Arc::new(v2_builder.finish()),
-        ],
+        vec![Arc::new(k1_array), Arc::new(id4_builder.finish()), v2_array],
     )
     .unwrap();
 
+    if sort_by_key {
+        batch = sort_by_keys(&batch, &["id1"]).map_err(ArrowError)?;
+    }
+
+    if dict_keys {
+        batch = dictionary_encode_keys(&batch).map_err(ArrowError)?;
+    }
+
     Ok(PyArrowType(batch))
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    n,
+    seed,
+    k1,
+    k2,
+    batch_size,
+    sort_by_key=false,
+    dict_keys=false,
+    int_measure=false,
+    disjoint=false,
+    measure_min=1.0,
+    measure_max=100.0,
+    measure_dist="uniform",
+))]
+#[allow(clippy::too_many_arguments)]
 fn generate_join_rhs_medium(
     n: i64,
     seed: i64,
     k1: PyArrowType<ArrayData>,
     k2: PyArrowType<ArrayData>,
     batch_size: i64,
+    sort_by_key: bool,
+    dict_keys: bool,
+    int_measure: bool,
+    disjoint: bool,
+    measure_min: f64,
+    measure_max: f64,
+    measure_dist: &str,
 ) -> PyResult<PyArrowType<RecordBatch>> {
     let k1_array: Int64Array = Int64Array::try_from(k1.0)?;
     let k2_array: Int64Array = Int64Array::try_from(k2.0)?;
+    validate_keys("k1", &k1_array)?;
+    validate_keys("k2", &k2_array)?;
+    // Keys are shifted out of the lhs key space for anti-join benchmarks
+    let (k1_array, k2_array) = if disjoint {
+        (
+            disjoint_keys(k1_array, join_key_levels(n / 1_000_000)),
+            disjoint_keys(k2_array, join_key_levels(n / 1_000)),
+        )
+    } else {
+        (k1_array, k2_array)
+    };
     let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
-    let distr_float = Uniform::<f64>::try_from(1.0..=100.0).map_err(|e| UniformError(e))?;
     let item_capacity = batch_size as usize; // validation is on the python side
+    let v2_array = join_measure(
+        int_measure,
+        measure_min,
+        measure_max,
+        measure_dist,
+        item_capacity,
+        &mut rng,
+    )?;
 
     assert!(
         k1_array.len() == item_capacity,
@@ -317,38 +4549,64 @@ fn generate_join_rhs_medium(
         Field::new("id2", DataType::Int64, false),
         Field::new("id4", DataType::Utf8, false),
         Field::new("id5", DataType::Utf8, false),
-        Field::new("v2", DataType::Float64, false),
+        Field::new("v2", v2_array.data_type().clone(), false),
     ]);
 
     let len_of_max_key = (n * 11 / 10 / 1_000_000).to_string().len() + 2; // id{}, where {} is a number from a vec
-    let mut id4_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut id5_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut v2_builder = Float64Builder::with_capacity(item_capacity);
+    let mut id4_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
+    let mut id5_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
 
     for _i in 0..item_capacity {
         id4_builder.append_value(format! {"id{}", k1_array.value(_i)});
         id5_builder.append_value(format! {"id{}", k2_array.value(_i)});
-        v2_builder.append_value(distr_float.sample(&mut rng));
     }
 
-    let batch = RecordBatch::try_new(
+    let mut batch = RecordBatch::try_new(
         Arc::new(schema),
         vec![
             Arc::new(k1_array),
             Arc::new(k2_array),
             Arc::new(id4_builder.finish()),
             Arc::new(id5_builder.finish()),
-            Arc::new(v2_builder.finish()),
+            v2_array,
         ],
     )
     .unwrap();
 
+    if sort_by_key {
+        batch = sort_by_keys(&batch, &["id1", "id2"]).map_err(ArrowError)?;
+    }
+
+    if dict_keys {
+        batch = dictionary_encode_keys(&batch).map_err(ArrowError)?;
+    }
+
     Ok(PyArrowType(batch))
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    n,
+    seed,
+    k1,
+    k2,
+    k3,
+    batch_size,
+    sort_by_key=false,
+    dict_keys=false,
+    int_measure=false,
+    disjoint=false,
+    measure_min=1.0,
+    measure_max=100.0,
+    measure_dist="uniform",
+))]
+#[allow(clippy::too_many_arguments)]
 fn generate_join_rhs_big(
     n: i64,
     seed: i64,
@@ -356,13 +4614,40 @@ fn generate_join_rhs_big(
     k2: PyArrowType<ArrayData>,
     k3: PyArrowType<ArrayData>,
     batch_size: i64,
+    sort_by_key: bool,
+    dict_keys: bool,
+    int_measure: bool,
+    disjoint: bool,
+    measure_min: f64,
+    measure_max: f64,
+    measure_dist: &str,
 ) -> PyResult<PyArrowType<RecordBatch>> {
     let k1_array: Int64Array = Int64Array::try_from(k1.0)?;
     let k2_array: Int64Array = Int64Array::try_from(k2.0)?;
     let k3_array: Int64Array = Int64Array::try_from(k3.0)?;
+    validate_keys("k1", &k1_array)?;
+    validate_keys("k2", &k2_array)?;
+    validate_keys("k3", &k3_array)?;
+    // Keys are shifted out of the lhs key space for anti-join benchmarks
+    let (k1_array, k2_array, k3_array) = if disjoint {
+        (
+            disjoint_keys(k1_array, join_key_levels(n / 1_000_000)),
+            disjoint_keys(k2_array, join_key_levels(n / 1_000)),
+            disjoint_keys(k3_array, join_key_levels(n)),
+        )
+    } else {
+        (k1_array, k2_array, k3_array)
+    };
     let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
-    let distr_float = Uniform::<f64>::try_from(1.0..=100.0).map_err(|e| UniformError(e))?;
     let item_capacity = batch_size as usize; // validation is on the python side
+    let v2_array = join_measure(
+        int_measure,
+        measure_min,
+        measure_max,
+        measure_dist,
+        item_capacity,
+        &mut rng,
+    )?;
 
     assert!(
         k1_array.len() == item_capacity,
@@ -390,26 +4675,30 @@ fn generate_join_rhs_big(
         Field::new("id4", DataType::Utf8, false),
         Field::new("id5", DataType::Utf8, false),
         Field::new("id6", DataType::Utf8, false),
-        Field::new("v2", DataType::Float64, false),
+        Field::new("v2", v2_array.data_type().clone(), false),
     ]);
 
     let len_of_max_key = (n * 11 / 10 / 1_000_000).to_string().len() + 2; // id{}, where {} is a number from a vec
-    let mut id4_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut id5_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut id6_builder =
-        StringBuilder::with_capacity(item_capacity, item_capacity * 8 * len_of_max_key); // utf8
-    let mut v2_builder = Float64Builder::with_capacity(item_capacity);
+    let mut id4_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
+    let mut id5_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
+    let mut id6_builder = StringBuilder::with_capacity(
+        item_capacity,
+        safe_string_capacity(item_capacity, 8 * len_of_max_key),
+    ); // utf8
 
     for _i in 0..item_capacity {
         id4_builder.append_value(format! {"id{}", k1_array.value(_i)});
         id5_builder.append_value(format! {"id{}", k2_array.value(_i)});
         id6_builder.append_value(format! {"id{}", k3_array.value(_i)});
-        v2_builder.append_value(distr_float.sample(&mut rng));
     }
 
-    let batch = RecordBatch::try_new(
+    let mut batch = RecordBatch::try_new(
         Arc::new(schema),
         vec![
             Arc::new(k1_array),
@@ -418,20 +4707,732 @@ fn generate_join_rhs_big(
             Arc::new(id4_builder.finish()),
             Arc::new(id5_builder.finish()),
             Arc::new(id6_builder.finish()),
-            Arc::new(v2_builder.finish()),
+            v2_array,
         ],
     )
     .unwrap();
 
+    if sort_by_key {
+        batch = sort_by_keys(&batch, &["id1", "id2", "id3"]).map_err(ArrowError)?;
+    }
+
+    if dict_keys {
+        batch = dictionary_encode_keys(&batch).map_err(ArrowError)?;
+    }
+
+    Ok(PyArrowType(batch))
+}
+
+/// Split a batch into partitions by fnv1a(key) % partitions, where key is an integer column.
+fn partition_by_key(
+    batch: &RecordBatch,
+    key: &str,
+    partitions: usize,
+) -> Result<Vec<RecordBatch>, arrow::error::ArrowError> {
+    let column = batch.column_by_name(key).ok_or_else(|| {
+        arrow::error::ArrowError::SchemaError(format!("column {} is not found", key))
+    })?;
+    let values = column.as_primitive_opt::<Int64Type>().ok_or_else(|| {
+        arrow::error::ArrowError::SchemaError(format!("column {} should be Int64", key))
+    })?;
+    let mut indices = vec![Vec::<u32>::new(); partitions];
+    for (i, value) in values.values().iter().enumerate() {
+        let partition = (fnv1a(&value.to_le_bytes()) % (partitions as u64)) as usize;
+        indices[partition].push(i as u32);
+    }
+    indices
+        .into_iter()
+        .map(|partition_indices| take_record_batch(batch, &UInt32Array::from(partition_indices)))
+        .collect()
+}
+
+/**
+Split a family of join tables into partitions with aligned keys for partition-wise joins.
+Each table containing the key column is hash-partitioned by it, so partition p of lhs
+only contains keys that land in partition p of every rhs table. Tables without the key
+column (for example, rhs_small for key "id2") are replicated to every partition,
+so the join of partition p of lhs with partition p of each rhs is still complete.
+
+:param lhs: pyarrow.RecordBatch
+    A batch of the left table, see generate_join_lhs.
+:param rhs_small: pyarrow.RecordBatch
+    A batch of the small right table, see generate_join_rhs_small.
+:param rhs_medium: pyarrow.RecordBatch
+    A batch of the medium right table, see generate_join_rhs_medium.
+:param rhs_big: pyarrow.RecordBatch
+    A batch of the big right table, see generate_join_rhs_big.
+:param partitions: int
+    An amount of partitions. Should be positive.
+:param key: str
+    An integer key column to partition by: "id1" (default), "id2" or "id3".
+
+:return: list[tuple[pyarrow.RecordBatch, pyarrow.RecordBatch, pyarrow.RecordBatch, pyarrow.RecordBatch]]
+    Tuples (lhs_part, rhs_small_part, rhs_medium_part, rhs_big_part) per partition.
+*/
+#[pyfunction]
+#[pyo3(signature = (lhs, rhs_small, rhs_medium, rhs_big, partitions, key="id1"))]
+#[allow(clippy::type_complexity)]
+fn generate_join_family(
+    lhs: PyArrowType<RecordBatch>,
+    rhs_small: PyArrowType<RecordBatch>,
+    rhs_medium: PyArrowType<RecordBatch>,
+    rhs_big: PyArrowType<RecordBatch>,
+    partitions: i64,
+    key: &str,
+) -> PyResult<
+    Vec<(
+        PyArrowType<RecordBatch>,
+        PyArrowType<RecordBatch>,
+        PyArrowType<RecordBatch>,
+        PyArrowType<RecordBatch>,
+    )>,
+> {
+    if partitions <= 0 {
+        return Err(PyValueError::new_err(format!(
+            "partitions should be positive, but got {}",
+            partitions
+        )));
+    }
+    if !["id1", "id2", "id3"].contains(&key) {
+        return Err(PyValueError::new_err(format!(
+            "key should be one of \"id1\", \"id2\", \"id3\", but got {}",
+            key
+        )));
+    }
+    let num_partitions = partitions as usize;
+    let mut parts = Vec::with_capacity(4);
+    for table in [&lhs.0, &rhs_small.0, &rhs_medium.0, &rhs_big.0] {
+        if table.schema().column_with_name(key).is_some() {
+            parts.push(partition_by_key(table, key, num_partitions).map_err(ArrowError)?);
+        } else {
+            parts.push(vec![table.clone(); num_partitions]);
+        }
+    }
+
+    let mut parts = parts.into_iter().map(|table_parts| table_parts.into_iter());
+    let (mut lhs_parts, mut small_parts, mut medium_parts, mut big_parts) = (
+        parts.next().unwrap(),
+        parts.next().unwrap(),
+        parts.next().unwrap(),
+        parts.next().unwrap(),
+    );
+    Ok((0..num_partitions)
+        .map(|_| {
+            (
+                PyArrowType(lhs_parts.next().unwrap()),
+                PyArrowType(small_parts.next().unwrap()),
+                PyArrowType(medium_parts.next().unwrap()),
+                PyArrowType(big_parts.next().unwrap()),
+            )
+        })
+        .collect())
+}
+
+/**
+Describe mismatches of join keys between schemas of generated join tables.
+Join keys are fields with names starting with "id". Every key of rhs should exist
+in lhs with the same type, for example a mismatch of id4 is described as
+"id4 is Utf8 in lhs but Int64 in rhs".
+
+:param lhs: pyarrow.Schema
+    A schema of the left table, see generate_join_lhs.
+:param rhs: pyarrow.Schema
+    A schema of one of the right tables.
+
+:return: list[str]
+    Human-readable descriptions of mismatches in the order of rhs fields,
+    empty if the keys match.
+*/
+#[pyfunction]
+fn diff_join_schemas(lhs: PyArrowType<Schema>, rhs: PyArrowType<Schema>) -> Vec<String> {
+    let (lhs, rhs) = (lhs.0, rhs.0);
+    rhs.fields()
+        .iter()
+        .filter(|field| field.name().starts_with("id"))
+        .filter_map(|field| match lhs.field_with_name(field.name()) {
+            Err(_) => Some(format!("{} is in rhs but not in lhs", field.name())),
+            Ok(lhs_field) if lhs_field.data_type() != field.data_type() => Some(format!(
+                "{} is {} in lhs but {} in rhs",
+                field.name(),
+                lhs_field.data_type(),
+                field.data_type()
+            )),
+            Ok(_) => None,
+        })
+        .collect()
+}
+
+/**
+Compute the exact amount of rows of the join of two batches as the ground truth for engines.
+Keys are compared by their values cast to strings, so both key columns should have
+the same type (dictionary-encoded keys are compared by their values). NULL keys never match,
+but rows with them are kept by outer sides.
+
+:param lhs: pyarrow.RecordBatch
+    A batch of the left table.
+:param rhs: pyarrow.RecordBatch
+    A batch of the right table.
+:param key: str
+    A name of the key column, should exist in both batches.
+:param join_type: str
+    A type of the join: "inner", "left", "right" or "outer".
+
+:return: int
+*/
+#[pyfunction]
+fn expected_join_rows(
+    lhs: PyArrowType<RecordBatch>,
+    rhs: PyArrowType<RecordBatch>,
+    key: &str,
+    join_type: &str,
+) -> PyResult<i64> {
+    if !["inner", "left", "right", "outer"].contains(&join_type) {
+        return Err(PyValueError::new_err(format!(
+            "join_type should be one of \"inner\", \"left\", \"right\", \"outer\", but got {}",
+            join_type
+        )));
+    }
+    let mut keys = Vec::with_capacity(2);
+    for (side, batch) in [("lhs", &lhs.0), ("rhs", &rhs.0)] {
+        let column = batch.column_by_name(key).ok_or_else(|| {
+            PyValueError::new_err(format!("key {} does not exist in {}", key, side))
+        })?;
+        keys.push(cast(column, &DataType::Utf8).map_err(ArrowError)?);
+    }
+    let (lhs_keys, rhs_keys) = (keys[0].as_string::<i32>(), keys[1].as_string::<i32>());
+
+    // Counts of rows per key value in lhs and rhs
+    let mut counts = HashMap::<&str, (i64, i64)>::new();
+    for value in lhs_keys.iter().flatten() {
+        counts.entry(value).or_default().0 += 1;
+    }
+    for value in rhs_keys.iter().flatten() {
+        counts.entry(value).or_default().1 += 1;
+    }
+    let matched = counts.values().map(|(l, r)| l * r).sum::<i64>();
+    let matched_lhs = counts
+        .values()
+        .filter(|(_, r)| *r > 0)
+        .map(|(l, _)| l)
+        .sum::<i64>();
+    let matched_rhs = counts
+        .values()
+        .filter(|(l, _)| *l > 0)
+        .map(|(_, r)| r)
+        .sum::<i64>();
+    let unmatched_lhs = lhs_keys.len() as i64 - matched_lhs;
+    let unmatched_rhs = rhs_keys.len() as i64 - matched_rhs;
+
+    Ok(match join_type {
+        "inner" => matched,
+        "left" => matched + unmatched_lhs,
+        "right" => matched + unmatched_rhs,
+        _ => matched + unmatched_lhs + unmatched_rhs,
+    })
+}
+
+/**
+Generate a star schema: a fact table with foreign keys and a dimension table per entry of dims.
+Dimension i has dims[i] rows with unique surrogate keys from 1-dims[i] in a random order
+and a label id{key}. Foreign key fk{i} of the fact table is sampled uniformly from the keys
+of dimension i, so every foreign key matches exactly one dimension row.
+Dimensions are generated from the seed and fact batches from seeds derived from it.
+
+:param fact_rows: int
+    A total amount of rows of the fact table. Should be positive.
+:param dims: list[int]
+    Amounts of rows of dimension tables. Should be non-empty and positive.
+:param seed: int
+    A random seed value.
+:param batch_size: int
+    A size of each batch of the fact table except the last one. Should be positive.
+
+:return: tuple[list[pyarrow.RecordBatch], list[pyarrow.RecordBatch]]
+    Batches of the fact table with columns fk0, ..., fkN and v1 (Float64 from [1, 100])
+    and a batch per dimension with columns key (Int64) and label (Utf8).
+*/
+#[pyfunction]
+#[allow(clippy::type_complexity)]
+fn generate_star_schema(
+    fact_rows: i64,
+    dims: Vec<i64>,
+    seed: i64,
+    batch_size: i64,
+) -> PyResult<(Vec<PyArrowType<RecordBatch>>, Vec<PyArrowType<RecordBatch>>)> {
+    if dims.is_empty() || dims.iter().any(|size| *size <= 0) {
+        return Err(PyValueError::new_err(format!(
+            "dims should be non-empty and positive, but got {:?}",
+            dims
+        )));
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
+    let dim_schema = Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Int64, false),
+        Field::new("label", DataType::Utf8, false),
+    ]));
+    let mut dim_batches = Vec::with_capacity(dims.len());
+    for size in &dims {
+        let mut keys = (1..=*size).collect::<Vec<i64>>();
+        keys.shuffle(&mut rng);
+        let labels = StringArray::from_iter_values(keys.iter().map(|key| format!("id{}", key)));
+        let batch = RecordBatch::try_new(
+            dim_schema.clone(),
+            vec![Arc::new(Int64Array::from(keys)), Arc::new(labels)],
+        )
+        .map_err(ArrowError)?;
+        dim_batches.push(PyArrowType(batch));
+    }
+
+    let mut fact_fields = (0..dims.len())
+        .map(|i| Field::new(format!("fk{}", i), DataType::Int64, false))
+        .collect::<Vec<Field>>();
+    fact_fields.push(Field::new("v1", DataType::Float64, false));
+    let fact_schema = Arc::new(Schema::new(fact_fields));
+    let batches = split_batches(fact_rows, seed, batch_size, true)?;
+    let mut fact_batches = Vec::with_capacity(batches.len());
+    for (batch_seed, size) in batches {
+        let mut rng = ChaCha8Rng::seed_from_u64(batch_seed as u64);
+        let mut columns = Vec::with_capacity(dims.len() + 1);
+        for dim_size in &dims {
+            let distr_key = Uniform::<i64>::try_from(1..=*dim_size).map_err(|e| UniformError(e))?;
+            let fk = (0..size)
+                .map(|_| distr_key.sample(&mut rng))
+                .collect::<Int64Array>();
+            columns.push(Arc::new(fk) as ArrayRef);
+        }
+        columns.push(join_measure(
+            false,
+            1.0,
+            100.0,
+            "uniform",
+            size as usize,
+            &mut rng,
+        )?);
+        let batch = RecordBatch::try_new(fact_schema.clone(), columns).map_err(ArrowError)?;
+        fact_batches.push(PyArrowType(batch));
+    }
+
+    Ok((fact_batches, dim_batches))
+}
+
+/**
+Get the version of falsa and the versions of libraries it was built with.
+It is useful to log it together with generated data, because the output
+may differ between versions.
+
+:return: dict
+    Keys are version, arrow_version (the version of arrow resolved in Cargo.lock
+    at build time or "unknown") and features (a list of enabled cargo features,
+    ["zstd"] for the default build).
+*/
+#[pyfunction]
+fn falsa_version_info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let info = PyDict::new(py);
+    info.set_item("version", env!("CARGO_PKG_VERSION"))?;
+    info.set_item("arrow_version", env!("FALSA_ARROW_VERSION"))?;
+    let features = env!("FALSA_FEATURES")
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .collect::<Vec<&str>>();
+    info.set_item("features", features)?;
+    Ok(info)
+}
+
+/**
+Generate a batch that fills an arbitrary schema instead of the built-in group-by one.
+Values depend only on the type of the field: integers are uniform from [1, 100],
+floats are uniform from [0, 100], strings are in form id123 with 123 from 1-100
+and booleans are true or false with equal probability. Fields are generated
+in the order of the schema from a single random stream seeded by seed.
+
+:param schema: pyarrow.Schema
+    A schema to fill. Supported types are signed and unsigned integers,
+    Float16, Float32, Float64, Utf8, LargeUtf8 and Boolean.
+:param rows: int
+    An amount of rows. Should be non-negative.
+:param seed: int
+    A random seed value.
+:param nas: int
+    A number from 0 to 100, the percent of NULLs in nullable fields.
+    Non-nullable fields never contain NULLs. 0 by default.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+#[pyo3(signature = (schema, rows, seed, nas=0))]
+fn generate_for_schema(
+    schema: PyArrowType<Schema>,
+    rows: i64,
+    seed: u64,
+    nas: i64,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let schema = Arc::new(schema.0);
+    if rows < 0 {
+        return Err(PyValueError::new_err(format!(
+            "rows should be non-negative, but got {}",
+            rows
+        )));
+    }
+    if !(0..=100).contains(&nas) {
+        return Err(PyValueError::new_err(format!(
+            "nas should be in [0, 100], but got {}",
+            nas
+        )));
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let distr_int = Uniform::<i64>::try_from(1..=100).map_err(|e| UniformError(e))?;
+    let distr_float = Uniform::<f64>::try_from(0.0..=100.0).map_err(|e| UniformError(e))?;
+    let distr_nas = Uniform::<i64>::try_from(0..100).map_err(|e| UniformError(e))?;
+    let item_capacity = rows as usize;
+
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let data_type = field.data_type();
+        let column: ArrayRef = if data_type.is_integer() {
+            let values = (0..item_capacity)
+                .map(|_| distr_int.sample(&mut rng))
+                .collect::<Int64Array>();
+            cast(&values, data_type).map_err(ArrowError)?
+        } else if data_type.is_floating() {
+            let values = (0..item_capacity)
+                .map(|_| distr_float.sample(&mut rng))
+                .collect::<Float64Array>();
+            cast(&values, data_type).map_err(ArrowError)?
+        } else {
+            match data_type {
+                DataType::Utf8 | DataType::LargeUtf8 => {
+                    let values = (0..item_capacity)
+                        .map(|_| Some(format!("id{:03}", distr_int.sample(&mut rng))))
+                        .collect::<StringArray>();
+                    cast(&values, data_type).map_err(ArrowError)?
+                }
+                DataType::Boolean => Arc::new(
+                    (0..item_capacity)
+                        .map(|_| Some(rng.random_bool(0.5)))
+                        .collect::<BooleanArray>(),
+                ),
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "type {} of the field {} is not supported",
+                        data_type,
+                        field.name()
+                    )))
+                }
+            }
+        };
+        let column = if field.is_nullable() {
+            with_sampled_nulls(&column, nas, &distr_nas, &mut rng).map_err(ArrowError)?
+        } else {
+            column
+        };
+        columns.push(column);
+    }
+
+    let batch = RecordBatch::try_new_with_options(
+        schema,
+        columns,
+        &RecordBatchOptions::new().with_row_count(Some(item_capacity)),
+    )
+    .map_err(ArrowError)?;
+
+    Ok(PyArrowType(batch))
+}
+
+/**
+Cast a generated batch to the given schema column by column.
+It allows to adapt the output to strict downstream schemas without regenerating data,
+for example Int64 to Int32 or Utf8 to LargeUtf8.
+
+:param batch: pyarrow.RecordBatch
+    A batch to cast.
+:param target: pyarrow.Schema
+    A target schema. Should have the same amount of fields as the batch.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn cast_batch(
+    batch: PyArrowType<RecordBatch>,
+    target: PyArrowType<Schema>,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batch = batch.0;
+    let target = target.0;
+    if batch.num_columns() != target.fields().len() {
+        return Err(PyValueError::new_err(format!(
+            "target schema should have {} fields, but got {}",
+            batch.num_columns(),
+            target.fields().len()
+        )));
+    }
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(target.fields().iter())
+        .map(|(column, field)| cast(column, field.data_type()))
+        .collect::<Result<Vec<ArrayRef>, arrow::error::ArrowError>>()
+        .map_err(ArrowError)?;
+    let casted = RecordBatch::try_new(Arc::new(target), columns).map_err(ArrowError)?;
+
+    Ok(PyArrowType(casted))
+}
+
+/**
+Concatenate generated batches vertically, reconciling small differences of schemas.
+Fields are matched by position and should have the same names. A field is nullable
+if it is nullable in any batch; integer fields of different widths are cast to Int64.
+Other differences of types are incompatible and raise an error.
+
+:param batches: list[pyarrow.RecordBatch]
+    Batches to concatenate. Should be non-empty.
+
+:return: pyarrow.RecordBatch
+*/
+#[pyfunction]
+fn concat_groupby_batches(
+    batches: Vec<PyArrowType<RecordBatch>>,
+) -> PyResult<PyArrowType<RecordBatch>> {
+    let batches = batches.into_iter().map(|batch| batch.0).collect::<Vec<_>>();
+    let Some(first) = batches.first() else {
+        return Err(PyValueError::new_err("batches should be non-empty"));
+    };
+
+    let mut fields = first
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect::<Vec<Field>>();
+    for batch in batches.iter().skip(1) {
+        let schema = batch.schema();
+        if schema.fields().len() != fields.len() {
+            return Err(PyValueError::new_err(format!(
+                "batches should have {} fields, but got {}",
+                fields.len(),
+                schema.fields().len()
+            )));
+        }
+        for (unified, field) in fields.iter_mut().zip(schema.fields().iter()) {
+            if unified.name() != field.name() {
+                return Err(PyValueError::new_err(format!(
+                    "field names do not match: {} != {}",
+                    unified.name(),
+                    field.name()
+                )));
+            }
+            let data_type = if unified.data_type() == field.data_type() {
+                field.data_type().clone()
+            } else if unified.data_type().is_integer() && field.data_type().is_integer() {
+                DataType::Int64
+            } else {
+                return Err(PyValueError::new_err(format!(
+                    "types of the field {} are incompatible: {} != {}",
+                    field.name(),
+                    unified.data_type(),
+                    field.data_type()
+                )));
+            };
+            let nullable = unified.is_nullable() || field.is_nullable();
+            *unified = Field::new(field.name(), data_type, nullable);
+        }
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let casted = batches
+        .iter()
+        .map(|batch| {
+            let columns = batch
+                .columns()
+                .iter()
+                .zip(schema.fields().iter())
+                .map(|(column, field)| cast(column, field.data_type()))
+                .collect::<Result<Vec<ArrayRef>, arrow::error::ArrowError>>()?;
+            RecordBatch::try_new(schema.clone(), columns)
+        })
+        .collect::<Result<Vec<RecordBatch>, arrow::error::ArrowError>>()
+        .map_err(ArrowError)?;
+    let batch = concat_batches(&schema, &casted).map_err(ArrowError)?;
+
     Ok(PyArrowType(batch))
 }
 
+/**
+Split a batch into near-equal slices for partition-parallel engines without copying.
+The first rows % parts slices have one more row than others, so lengths differ
+by at most one and concatenated slices give the original batch.
+
+:param batch: pyarrow.RecordBatch
+    A batch to split.
+:param parts: int
+    An amount of slices. Should be positive. Slices are empty if parts exceeds rows.
+
+:return: list[pyarrow.RecordBatch]
+*/
+#[pyfunction]
+fn split_batch(
+    batch: PyArrowType<RecordBatch>,
+    parts: i64,
+) -> PyResult<Vec<PyArrowType<RecordBatch>>> {
+    if parts < 1 {
+        return Err(PyValueError::new_err(format!(
+            "parts should be positive, but got {}",
+            parts
+        )));
+    }
+    let batch = batch.0;
+    let parts = parts as usize;
+    let (base, extra) = (batch.num_rows() / parts, batch.num_rows() % parts);
+    let mut offset = 0;
+    let slices = (0..parts)
+        .map(|part| {
+            let length = base + (part < extra) as usize;
+            let slice = batch.slice(offset, length);
+            offset += length;
+            PyArrowType(slice)
+        })
+        .collect();
+
+    Ok(slices)
+}
+
+/**
+Export columns of a batch through the Arrow C Data Interface for zero-copy handoff
+to C/C++ engines. Each column is exported as a pair of pointers to ArrowArray
+and ArrowSchema structs allocated on the heap. The consumer takes ownership of both:
+it should call their release callbacks and free the structs, otherwise memory leaks.
+
+:param batch: pyarrow.RecordBatch
+    A batch to export.
+
+:return: list[tuple[str, int, int]]
+    Tuples (column name, ArrowArray address, ArrowSchema address) per column.
+*/
+#[pyfunction]
+fn export_batch_c_data(batch: PyArrowType<RecordBatch>) -> PyResult<Vec<(String, usize, usize)>> {
+    let batch = batch.0;
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, column)| {
+            let (array, schema) = to_ffi(&column.to_data()).map_err(ArrowError)?;
+            let array = Box::into_raw(Box::new(array)) as usize;
+            let schema = Box::into_raw(Box::new(schema)) as usize;
+            Ok((field.name().clone(), array, schema))
+        })
+        .collect()
+}
+
+/**
+Export raw values of numeric columns of a batch as contiguous bytes for wrapping
+them as tensors. Bytes are copied from Arrow value buffers in the native byte order
+and byte-swapped when another order is requested with endian.
+Values of NULL slots are unspecified, so callers should use the validity of columns.
+
+:param batch: pyarrow.RecordBatch
+    A batch to export. Non-numeric columns are skipped.
+:param endian: str
+    Byte order of exported values: "native" (default), "little" or "big".
+    Values are byte-swapped per element when it differs from the native order.
+
+:return: list[tuple[str, str, bytes]]
+    Tuples (column name, Arrow type, bytes) per numeric column, where the length
+    of bytes is rows multiplied by the size of an element of the type.
+*/
+#[pyfunction]
+#[pyo3(signature = (batch, endian="native"))]
+fn numeric_column_bytes(
+    batch: PyArrowType<RecordBatch>,
+    endian: &str,
+) -> PyResult<Vec<(String, String, Vec<u8>)>> {
+    let swap = match endian {
+        "native" => false,
+        "little" => cfg!(target_endian = "big"),
+        "big" => cfg!(target_endian = "little"),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "endian should be one of native, little or big, but got {}",
+                endian
+            )))
+        }
+    };
+    let batch = batch.0;
+    Ok(batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .filter_map(|(field, column)| {
+            let width = field
+                .data_type()
+                .primitive_width()
+                .filter(|_| field.data_type().is_numeric())?;
+            let data = column.to_data();
+            let start = data.offset() * width;
+            let mut bytes =
+                data.buffers()[0].as_slice()[start..start + data.len() * width].to_vec();
+            if swap {
+                bytes
+                    .chunks_exact_mut(width)
+                    .for_each(|element| element.reverse());
+            }
+            Some((field.name().clone(), field.data_type().to_string(), bytes))
+        })
+        .collect())
+}
+
 #[pymodule]
 fn native(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(generate_groupby, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_reader, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_stream, m)?)?;
+    m.add_class::<GroupByStream>()?;
+    m.add_class::<GenerationStats>()?;
+    m.add_class::<GroupByByteBudgetReader>()?;
+    m.add_class::<MixedWorkloadReader>()?;
+    m.add_function(wrap_pyfunction!(generate_groupby_from_state, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_all_null_groupby, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_segments, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_shard, m)?)?;
+    m.add_function(wrap_pyfunction!(preview_groupby, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_rng_state, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        generate_groupby_to_parquet_partitioned,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(append_groupby_to_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_to_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_to_object_store, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_to_ipc, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_to_shm, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_v3_quantiles, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_draws_per_row, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_range, m)?)?;
+    m.add_function(wrap_pyfunction!(expected_null_fraction, m)?)?;
+    m.add_function(wrap_pyfunction!(deterministic_permutation, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_value_column, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_groupby_values_only, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_ground_truth, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_predicate_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(groupby_cardinalities, m)?)?;
+    m.add_function(wrap_pyfunction!(cast_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_for_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(concat_groupby_batches, m)?)?;
+    m.add_function(wrap_pyfunction!(split_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(export_batch_c_data, m)?)?;
+    m.add_function(wrap_pyfunction!(numeric_column_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(falsa_version_info, m)?)?;
     m.add_function(wrap_pyfunction!(generate_join_lhs, m)?)?;
     m.add_function(wrap_pyfunction!(generate_join_rhs_small, m)?)?;
     m.add_function(wrap_pyfunction!(generate_join_rhs_medium, m)?)?;
     m.add_function(wrap_pyfunction!(generate_join_rhs_big, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_join_family, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_join_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(expected_join_rows, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_star_schema, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests;
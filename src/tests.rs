@@ -0,0 +1,2243 @@
+use super::*;
+use arrow::array::AsArray;
+use arrow::compute::{lexsort_to_indices, take_record_batch, SortColumn};
+use arrow::datatypes::{Float64Type, Int64Type};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Default parameters of generate_groupby with the given shape.
+fn params(n: i64, k: i64, nas: i64, seed: i64, batch_size: i64) -> GroupByParams<'static> {
+    GroupByParams {
+        n,
+        k,
+        nas,
+        seed,
+        batch_size,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn v3_round_keeps_two_decimals() {
+    let batch = groupby_with_params(GroupByParams {
+        v3_round: Some(2),
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    for v in batch.column(8).as_primitive::<Float64Type>().values() {
+        assert!(((v * 100.0).round() - v * 100.0).abs() < 1e-6, "{}", v);
+    }
+}
+
+#[test]
+fn map_column_holds_two_entries() {
+    let batch = groupby_with_params(GroupByParams {
+        with_map: true,
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    let m1 = batch.column_by_name("m1").unwrap();
+    assert!(matches!(m1.data_type(), DataType::Map(_, false)));
+    let m1 = m1.as_map();
+    for row in 0..m1.len() {
+        let entries = m1.value(row);
+        let keys = entries.column(0).as_string::<i32>();
+        let values = entries.column(1).as_primitive::<Int64Type>();
+        assert_eq!(keys.iter().collect::<Vec<_>>(), [Some("a"), Some("b")]);
+        assert!((1..=5).contains(&values.value(0)));
+        assert!((1..=15).contains(&values.value(1)));
+    }
+}
+
+#[test]
+fn nas_string_and_nas_int_are_separate() {
+    let batch = groupby_with_params(GroupByParams {
+        nas_string: Some(50),
+        nas_int: Some(0),
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    for column in &batch.columns()[..3] {
+        assert!(column.null_count() > 0);
+    }
+    for column in &batch.columns()[3..6] {
+        assert_eq!(column.null_count(), 0);
+    }
+}
+
+#[test]
+fn shuffle_keeps_the_content() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let default = groupby_with_params(base.clone()).unwrap();
+    let shuffled = groupby_with_params(GroupByParams {
+        shuffle: true,
+        ..base
+    })
+    .unwrap();
+    assert_ne!(shuffled, default);
+    let sorted = |batch: &RecordBatch| {
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|column| SortColumn {
+                values: column.clone(),
+                options: None,
+            })
+            .collect::<Vec<SortColumn>>();
+        let indices = lexsort_to_indices(&columns, None).unwrap();
+        take_record_batch(batch, &indices).unwrap()
+    };
+    assert_eq!(sorted(&shuffled), sorted(&default));
+}
+
+#[test]
+fn duration_column_is_bounded() {
+    let batch = groupby_with_params(GroupByParams {
+        with_duration: true,
+        dur_min: 10,
+        dur_max: 20,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let dur = batch.column_by_name("dur").unwrap();
+    assert_eq!(dur.data_type(), &DataType::Duration(TimeUnit::Second));
+    let dur = dur.as_primitive::<arrow::datatypes::DurationSecondType>();
+    assert!(dur.null_count() > 0);
+    assert!(dur.iter().flatten().all(|v| (10..=20).contains(&v)));
+}
+
+#[test]
+fn batch_size_is_capped_by_n() {
+    let batch = groupby_with_params(params(100, 10, 0, 42, 1_000)).unwrap();
+    assert_eq!(batch.num_rows(), 100);
+}
+
+/// Keys of join generators as passed from python.
+fn join_keys(values: impl IntoIterator<Item = i64>) -> PyArrowType<ArrayData> {
+    PyArrowType(Int64Array::from_iter_values(values).into_data())
+}
+
+#[test]
+fn sorted_join_keys_are_non_decreasing() {
+    let is_sorted = |batch: &RecordBatch| {
+        let id1 = batch.column_by_name("id1").unwrap();
+        id1.as_primitive::<Int64Type>().values().is_sorted()
+    };
+    let lhs = generate_join_lhs(
+        1_000,
+        42,
+        join_keys((0..1_000).map(|i| i % 10 + 1)),
+        join_keys((0..1_000).map(|i| i % 100 + 1)),
+        join_keys((1..=1_000).rev()),
+        1_000,
+        true,
+        false,
+        false,
+        1.0,
+        100.0,
+        "uniform",
+    )
+    .unwrap();
+    assert!(is_sorted(&lhs.0));
+    let rhs = generate_join_rhs_small(
+        1_000,
+        42,
+        join_keys((1..=100).rev()),
+        100,
+        true,
+        false,
+        false,
+        false,
+        1.0,
+        100.0,
+        "uniform",
+    )
+    .unwrap();
+    assert!(is_sorted(&rhs.0));
+}
+
+#[test]
+fn key_stride_multiplies_keys() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let batch = groupby_with_params(GroupByParams {
+        key_stride: 1_000,
+        ..base.clone()
+    })
+    .unwrap();
+    let id4 = batch.column(3).as_primitive::<Int64Type>();
+    assert!(id4.iter().flatten().all(|v| v % 1_000 == 0));
+    assert!(groupby_with_params(GroupByParams {
+        key_stride: i64::MAX / 10,
+        ..base
+    })
+    .is_err());
+}
+
+#[test]
+fn reader_is_consumed_through_c_stream() {
+    let reader = generate_groupby_reader(10_000, 100, 10, 42, 3_000, false, 0, true, false)
+        .unwrap()
+        .0;
+    let stream = arrow::ffi_stream::FFI_ArrowArrayStream::new(reader);
+    let reader = arrow::ffi_stream::ArrowArrayStreamReader::try_new(stream).unwrap();
+    let rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+    assert_eq!(rows, 10_000);
+}
+
+/// A fresh directory for the output files of a test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("falsa-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Read all the rows of a parquet file.
+fn read_parquet(path: &Path) -> Vec<RecordBatch> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<Vec<RecordBatch>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn partitioned_buckets_sum_to_n() {
+    let dir = test_dir("partitioned");
+    let counts =
+        generate_groupby_to_parquet_partitioned(dir.clone(), 10_000, 100, 10, 42, 3_000, 4)
+            .unwrap();
+    assert_eq!(counts.len(), 5);
+    assert_eq!(counts.iter().sum::<i64>(), 10_000);
+    let mut files = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    // part-null.parquet sorts after the numbered buckets, like its count
+    for (file, count) in files.iter().zip(&counts) {
+        let rows = read_parquet(file)
+            .iter()
+            .map(|b| b.num_rows())
+            .sum::<usize>();
+        assert_eq!(rows as i64, *count);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn v3_quantiles_match_sorted_values() {
+    let quantiles = groupby_v3_quantiles(10_000, 100, 0, 42, 1_001, vec![0.0, 0.5, 1.0]).unwrap();
+    let batch = groupby_batch(10_000, 100, 0, 42, 1_001, None, None).unwrap();
+    let mut values = batch
+        .column(8)
+        .as_primitive::<Float64Type>()
+        .values()
+        .to_vec();
+    values.sort_by(|a, b| a.total_cmp(b));
+    assert_eq!(quantiles, [values[0], values[500], values[1_000]]);
+    assert!(groupby_v3_quantiles(10_000, 100, 0, 42, 1_001, vec![1.5]).is_err());
+}
+
+#[test]
+fn v3_nan_count_follows_rate() {
+    let batch = groupby_with_params(GroupByParams {
+        v3_nan_rate: 0.2,
+        ..params(100_000, 100, 0, 42, 10_000)
+    })
+    .unwrap();
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+    let nans = v3.values().iter().filter(|v| v.is_nan()).count();
+    assert!((1_800..2_200).contains(&nans), "{} NaNs", nans);
+}
+
+#[test]
+fn ts_increases_across_streamed_batches() {
+    let reader = GroupByReader::try_new(2_000, 100, 0, 42, 1_000, Some(0), true, false).unwrap();
+    let mut ts = Vec::new();
+    for batch in reader {
+        let batch = batch.unwrap();
+        let column = batch.column_by_name("ts").unwrap();
+        ts.extend_from_slice(column.as_primitive::<TimestampMicrosecondType>().values());
+    }
+    assert_eq!(ts.len(), 2_000);
+    assert!(ts.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn join_keys_with_nulls_are_rejected() {
+    pyo3::prepare_freethreaded_python();
+    let mut keys = vec![Some(1); 100];
+    keys[10] = None;
+    let error = generate_join_rhs_small(
+        1_000,
+        42,
+        PyArrowType(Int64Array::from(keys).into_data()),
+        100,
+        false,
+        false,
+        false,
+        false,
+        1.0,
+        100.0,
+        "uniform",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ValueError: k1 should not contain NULLs, but got 1 NULLs"
+    );
+}
+
+#[test]
+fn hex_ids_are_lowercase_hex() {
+    let batch = groupby_with_params(GroupByParams {
+        hex_ids: true,
+        ..params(100_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    for column in &batch.columns()[..3] {
+        for value in column.as_string::<i32>().iter().flatten() {
+            let digits = value.strip_prefix("id").unwrap();
+            assert!(!digits.is_empty());
+            assert!(digits.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+        }
+    }
+}
+
+#[test]
+fn unique_id3_has_no_duplicates() {
+    let batch = groupby_with_params(GroupByParams {
+        unique_id3: true,
+        ..params(100_000, 10, 0, 42, 1_000)
+    })
+    .unwrap();
+    let id3 = batch.column(2).as_string::<i32>();
+    assert_eq!(id3.iter().collect::<HashSet<_>>().len(), 1_000);
+}
+
+#[test]
+fn unique_id3_rejects_repeated_values() {
+    let base = GroupByParams {
+        unique_id3: true,
+        ..params(100_000, 10, 0, 42, 1_000)
+    };
+    assert!(groupby_with_params(GroupByParams {
+        target_compressibility: 0.5,
+        ..base.clone()
+    })
+    .is_err());
+    assert!(groupby_with_params(GroupByParams {
+        corrupt_rate: 0.1,
+        corrupt_target: Some("id3"),
+        ..base
+    })
+    .is_err());
+}
+
+#[test]
+fn cast_batch_narrows_int_ids() {
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    let fields = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            DataType::Int64 => field.as_ref().clone().with_data_type(DataType::Int32),
+            _ => field.as_ref().clone(),
+        })
+        .collect::<Vec<Field>>();
+    let casted = cast_batch(PyArrowType(batch.clone()), PyArrowType(Schema::new(fields)))
+        .unwrap()
+        .0;
+    assert_eq!(casted.column(3).data_type(), &DataType::Int32);
+    let id4 = cast(casted.column(3), &DataType::Int64).unwrap();
+    assert_eq!(&id4, batch.column(3));
+}
+
+#[test]
+fn id4_id5_corr_sets_equal_fraction() {
+    let batch = groupby_with_params(GroupByParams {
+        id4_id5_corr: 0.5,
+        ..params(100_000, 100, 0, 42, 10_000)
+    })
+    .unwrap();
+    let id4 = batch.column(3).as_primitive::<Int64Type>().values();
+    let id5 = batch.column(4).as_primitive::<Int64Type>().values();
+    let equal = id4.iter().zip(id5.iter()).filter(|(a, b)| a == b).count();
+    // 0.5 plus independent matches of 1 / K in the other half
+    assert!((4_800..5_300).contains(&equal), "{} equal", equal);
+}
+
+#[test]
+fn blobs_have_exact_length() {
+    let batch = groupby_with_params(GroupByParams {
+        blob_bytes: 16,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let blob = batch.column_by_name("blob").unwrap().as_binary::<i64>();
+    assert!(blob.null_count() > 0);
+    assert!(blob.iter().flatten().all(|value| value.len() == 16));
+}
+
+#[test]
+fn ground_truth_matches_raw_batch() {
+    let truth = groupby_ground_truth(10_000, 100, 10, 42, 1_000).unwrap().0;
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    let id1 = batch.column(0).as_string::<i32>();
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    let mut expected = HashMap::<Option<&str>, (i64, i64)>::new();
+    for (key, value) in id1.iter().zip(v1.values()) {
+        let group = expected.entry(key).or_default();
+        group.0 += 1;
+        group.1 += value;
+    }
+    let keys = truth.column(0).as_string::<i32>();
+    let counts = truth.column(1).as_primitive::<Int64Type>();
+    let v1_sums = truth.column(2).as_primitive::<Int64Type>();
+    assert_eq!(truth.num_rows(), expected.len());
+    for i in 0..truth.num_rows() {
+        let key = (!keys.is_null(i)).then(|| keys.value(i));
+        assert_eq!(expected[&key], (counts.value(i), v1_sums.value(i)));
+    }
+}
+
+#[test]
+fn rng_state_round_trip_matches_seed() {
+    let state = groupby_rng_state(42);
+    let restored = generate_groupby_from_state(&state, 10_000, 100, 10, 1_000)
+        .unwrap()
+        .0;
+    let seeded = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    assert_eq!(restored, seeded);
+}
+
+#[test]
+fn salted_batches_differ_but_reproduce() {
+    let batches = || {
+        GroupByReader::try_new(2_000, 100, 10, 42, 1_000, None, true, false)
+            .unwrap()
+            .collect::<Result<Vec<RecordBatch>, _>>()
+            .unwrap()
+    };
+    let first = batches();
+    assert_ne!(first[0], first[1]);
+    assert_eq!(first, batches());
+}
+
+#[test]
+fn feature_vectors_have_feature_dim() {
+    let batch = groupby_with_params(GroupByParams {
+        feature_dim: 4,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let feat = batch.column_by_name("feat").unwrap().as_fixed_size_list();
+    assert_eq!(feat.value_length(), 4);
+    assert_eq!(feat.value_type(), DataType::Float64);
+    assert_eq!(feat.values().len(), 4 * 1_000);
+}
+
+#[test]
+fn fast_nulls_are_deterministic() {
+    let base = GroupByParams {
+        fast_nulls: true,
+        ..params(100_000, 100, 10, 42, 10_000)
+    };
+    let batch = groupby_with_params(base.clone()).unwrap();
+    assert_eq!(batch, groupby_with_params(base.clone()).unwrap());
+    assert_ne!(
+        batch,
+        groupby_with_params(GroupByParams { seed: 7, ..base }).unwrap()
+    );
+    for column in &batch.columns()[..6] {
+        assert!((800..1_200).contains(&column.null_count()));
+    }
+}
+
+/// Generate a batch and count 32-bit words it consumed from the main random stream.
+fn consumed_words(params: GroupByParams) -> (RecordBatch, u128) {
+    let mut rng = ChaCha8Rng::seed_from_u64(params.seed as u64);
+    let batch = groupby_with_rng(params, &mut rng).unwrap();
+    (batch, rng.get_word_pos())
+}
+
+#[test]
+fn draws_per_row_match_consumed_words() {
+    let rows = 200;
+    let (_, words) = consumed_words(params(1_000, 10, 0, 42, rows));
+    let expected = groupby_draws_per_row(
+        false, false, false, false, false, false, false, 0, 0, false, false, false, false,
+    );
+    assert_eq!(words, (rows as u64 * expected) as u128);
+
+    let (_, words) = consumed_words(GroupByParams {
+        fast_nulls: true,
+        ..params(1_000, 10, 10, 42, rows)
+    });
+    let expected = groupby_draws_per_row(
+        true, true, false, false, false, false, false, 0, 0, false, false, false, false,
+    );
+    assert_eq!(words, (rows as u64 * expected) as u128);
+
+    let (_, words) = consumed_words(GroupByParams {
+        v3_nan_rate: 0.5,
+        with_map: true,
+        with_duration: true,
+        monotonic_ts: true,
+        blob_bytes: 5,
+        feature_dim: 3,
+        with_time: true,
+        key_dist: "pareto",
+        target_compressibility: 0.5,
+        ..params(1_000, 10, 0, 42, rows)
+    });
+    let expected = groupby_draws_per_row(
+        false, false, true, false, true, true, true, 5, 3, true, false, true, false,
+    );
+    // ts and target_compressibility do not draw in the first row
+    assert_eq!(words, (rows as u64 * expected - 4) as u128);
+
+    let (batch, words) = consumed_words(GroupByParams {
+        v3_edge_rate: 0.3,
+        with_string_list: true,
+        ..params(1_000, 10, 0, 42, rows)
+    });
+    let expected = groupby_draws_per_row(
+        false, false, false, false, false, false, false, 0, 0, false, true, false, true,
+    );
+    let edges = batch
+        .column_by_name("v3")
+        .unwrap()
+        .as_primitive::<Float64Type>()
+        .values()
+        .iter()
+        .filter(|v| V3_EDGE_VALUES.iter().any(|e| e.to_bits() == v.to_bits()))
+        .count() as u64;
+    let tags = batch
+        .column_by_name("tags")
+        .unwrap()
+        .as_list::<i32>()
+        .values()
+        .len() as u64;
+    assert!(edges > 0 && tags > 0);
+    // Each edge value takes one more word and each tag one more draw
+    assert_eq!(words, (rows as u64 * expected + edges + 2 * tags) as u128);
+}
+
+#[test]
+fn all_null_batch_has_only_null_ids() {
+    let batch = generate_all_null_groupby(100, 42, 1_000).unwrap().0;
+    for column in &batch.columns()[..6] {
+        assert_eq!(column.null_count(), 1_000);
+    }
+    assert_eq!(batch.column(6).null_count(), 0);
+}
+
+#[test]
+fn id2_comes_from_the_given_values() {
+    let batch = groupby_with_params(GroupByParams {
+        id2_values: Some(vec![3, 7, 11]),
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let id2 = batch.column(1).as_string::<i32>();
+    let values = id2.iter().flatten().collect::<HashSet<&str>>();
+    assert_eq!(values, HashSet::from(["id003", "id007", "id011"]));
+}
+
+#[test]
+fn force_nullable_declares_every_field_nullable() {
+    let batch = groupby_with_params(GroupByParams {
+        force_nullable: true,
+        with_map: true,
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    assert!(batch
+        .schema()
+        .fields()
+        .iter()
+        .all(|field| field.is_nullable()));
+    assert!(batch
+        .columns()
+        .iter()
+        .all(|column| column.null_count() == 0));
+}
+
+#[test]
+fn appended_runs_are_read_back() {
+    let dir = test_dir("append");
+    let path = dir.join("groupby.parquet");
+    assert_eq!(
+        append_groupby_to_parquet(path.clone(), 1_000, 10, 5, 1, 300).unwrap(),
+        1_000
+    );
+    assert_eq!(
+        append_groupby_to_parquet(path.clone(), 500, 10, 5, 2, 300).unwrap(),
+        1_500
+    );
+    let rows = read_parquet(&path)
+        .iter()
+        .map(|batch| batch.num_rows())
+        .sum::<usize>();
+    assert_eq!(rows, 1_500);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_append_removes_the_temporary_file() {
+    let dir = test_dir("append_failed");
+    let path = dir.join("groupby.parquet");
+    std::fs::write(&path, b"not a parquet file").unwrap();
+    assert!(append_groupby_to_parquet(path.clone(), 1_000, 10, 5, 1, 300).is_err());
+    assert!(!dir.join("groupby.parquet.tmp").exists());
+    assert_eq!(std::fs::read(&path).unwrap(), b"not a parquet file");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn pareto_keys_are_heavy_tailed() {
+    let top_share = |key_dist| {
+        let batch = groupby_with_params(GroupByParams {
+            key_dist,
+            ..params(100_000, 100, 0, 42, 10_000)
+        })
+        .unwrap();
+        let mut counts = HashMap::<i64, usize>::new();
+        for key in batch.column(3).as_primitive::<Int64Type>().values() {
+            *counts.entry(*key).or_default() += 1;
+        }
+        *counts.values().max().unwrap() as f64 / batch.num_rows() as f64
+    };
+    assert!(top_share("uniform") < 0.05);
+    assert!(top_share("pareto") > 0.2);
+}
+
+#[test]
+fn version_info_reports_build() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let info = falsa_version_info(py).unwrap();
+        let version = info.get_item("version").unwrap().unwrap();
+        assert_eq!(
+            version.extract::<String>().unwrap(),
+            env!("CARGO_PKG_VERSION")
+        );
+        let arrow_version = info.get_item("arrow_version").unwrap().unwrap();
+        assert!(arrow_version
+            .extract::<String>()
+            .unwrap()
+            .starts_with("55."));
+        let features = info.get_item("features").unwrap().unwrap();
+        let expected = if cfg!(feature = "zstd") {
+            vec!["zstd".to_string()]
+        } else {
+            vec![]
+        };
+        assert_eq!(features.extract::<Vec<String>>().unwrap(), expected);
+    });
+}
+
+#[test]
+fn ceil_gives_larger_id6_domain_than_floor() {
+    let distinct_id6 = |nk_round| {
+        let batch = groupby_with_params(GroupByParams {
+            nk_round,
+            ..params(150, 100, 0, 42, 150)
+        })
+        .unwrap();
+        let id6 = batch.column(5).as_primitive::<Int64Type>();
+        id6.values().iter().collect::<HashSet<_>>().len()
+    };
+    assert_eq!(distinct_id6("floor"), 1);
+    assert_eq!(distinct_id6("ceil"), 2);
+}
+
+#[test]
+fn time_values_fall_within_one_day() {
+    let batch = groupby_with_params(GroupByParams {
+        with_time: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let t1 = batch.column_by_name("t1").unwrap();
+    assert_eq!(t1.data_type(), &DataType::Time64(TimeUnit::Microsecond));
+    let t1 = t1.as_primitive::<arrow::datatypes::Time64MicrosecondType>();
+    assert!(t1.null_count() > 0);
+    assert!(t1
+        .iter()
+        .flatten()
+        .all(|v| (0..86_400_000_000).contains(&v)));
+}
+
+#[test]
+fn column_seed_changes_only_its_column() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let default = groupby_with_params(base.clone()).unwrap();
+    for (name, index) in [("id1", 0), ("id4", 3), ("v3", 8)] {
+        let batch = groupby_with_params(GroupByParams {
+            column_seeds: Some([(name.to_string(), 7)].into_iter().collect()),
+            ..base.clone()
+        })
+        .unwrap();
+        for column in 0..default.num_columns() {
+            if column == index {
+                assert_ne!(batch.column(column), default.column(column));
+            } else {
+                assert_eq!(batch.column(column), default.column(column));
+            }
+        }
+    }
+}
+
+#[test]
+fn join_dictionary_keys_share_one_dictionary() {
+    let keys = |values: Vec<i64>| PyArrowType(Int64Array::from(values).into_data());
+    let generate = |dict_keys: bool| {
+        generate_join_lhs(
+            1_000,
+            42,
+            keys((1..=100).collect()),
+            keys((1..=100).rev().collect()),
+            keys((1..=100).map(|key| key * 7).collect()),
+            100,
+            false,
+            dict_keys,
+            false,
+            1.0,
+            100.0,
+            "uniform",
+        )
+        .unwrap()
+        .0
+    };
+    let plain = generate(false);
+    let encoded = generate(true);
+    let dictionary = encoded
+        .column_by_name("id4")
+        .unwrap()
+        .as_dictionary::<Int32Type>();
+    for name in ["id4", "id5", "id6"] {
+        let column = encoded.column_by_name(name).unwrap();
+        assert!(Arc::ptr_eq(
+            column.as_dictionary::<Int32Type>().values(),
+            dictionary.values()
+        ));
+        let decoded = cast(column, &DataType::Utf8).unwrap();
+        assert_eq!(&decoded, plain.column_by_name(name).unwrap());
+    }
+}
+
+#[test]
+fn segments_concatenate_all_rows() {
+    let uniform = groupby_batch(1_000, 10, 5, 1, 300, None, None).unwrap();
+    let pareto = groupby_with_params(GroupByParams {
+        key_dist: "pareto",
+        ..params(1_000, 10, 5, 2, 200)
+    })
+    .unwrap();
+    let batch = concat_segments(&[uniform.clone(), pareto]).unwrap();
+    assert_eq!(batch.num_rows(), 500);
+    let with_map = groupby_with_params(GroupByParams {
+        with_map: true,
+        ..params(1_000, 10, 5, 2, 200)
+    })
+    .unwrap();
+    assert!(concat_segments(&[uniform, with_map]).is_err());
+}
+
+#[test]
+fn wide_k_widens_id1() {
+    let batch = groupby_batch(1_000_000, 5_000, 0, 42, 50_000, None, None).unwrap();
+    let id1 = batch.column(0).as_string::<i32>();
+    assert!(id1.iter().any(|value| value == Some("id5000")));
+    // Every value has the widened width, which the capacity is reserved for
+    assert!(id1.iter().all(|value| value.unwrap().len() == 6));
+    assert!(id1.values().capacity() >= 6 * 50_000);
+}
+
+#[test]
+fn float16_v3_is_in_range() {
+    let batch = groupby_with_params(GroupByParams {
+        float16: true,
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    let v3 = batch.column(8);
+    assert_eq!(v3.data_type(), &DataType::Float16);
+    let v3 = v3.as_primitive::<arrow::datatypes::Float16Type>();
+    assert!(v3
+        .values()
+        .iter()
+        .all(|value| (0.0..=100.0).contains(&value.to_f64())));
+}
+
+#[test]
+fn preview_is_the_head_of_the_batch() {
+    let preview = preview_groupby(10_000, 100, 10, 42, 10).unwrap().0;
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    assert_eq!(preview, batch.slice(0, 10));
+}
+
+#[test]
+fn threads_write_identical_files() {
+    let dir = test_dir("threads");
+    let write = |threads| {
+        let path = dir.join(format!("groupby-{}.parquet", threads));
+        generate_groupby_to_parquet(
+            path.clone(),
+            10_000,
+            100,
+            10,
+            42,
+            1_000,
+            threads,
+            "uncompressed",
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        std::fs::read(path).unwrap()
+    };
+    assert_eq!(write(1), write(3));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn null_sentinel_replaces_nulls() {
+    let batch = groupby_with_params(GroupByParams {
+        null_sentinel: Some("-1".to_string()),
+        ..params(100_000, 100, 10, 42, 10_000)
+    })
+    .unwrap();
+    for column in &batch.columns()[..3] {
+        assert_eq!(column.null_count(), 0);
+        let sentinels = column
+            .as_string::<i32>()
+            .iter()
+            .filter(|value| *value == Some("-1"))
+            .count();
+        assert!((800..1_200).contains(&sentinels), "{} sentinels", sentinels);
+    }
+    for column in &batch.columns()[3..6] {
+        assert_eq!(column.null_count(), 0);
+        let column = column.as_primitive::<Int64Type>();
+        let sentinels = column.values().iter().filter(|value| **value == -1).count();
+        assert!((800..1_200).contains(&sentinels), "{} sentinels", sentinels);
+    }
+}
+
+#[test]
+fn stream_stats_count_all_rows() {
+    let mut stream =
+        generate_groupby_stream(2_500, 100, 10, 42, 1_000, false, 0, true, false).unwrap();
+    let mut nulls = 0;
+    while let Some(batch) = stream.__next__().unwrap() {
+        nulls += batch.0.column(0).null_count() as i64;
+    }
+    let stats = stream.stats();
+    assert_eq!(stats.rows, 2_500);
+    assert_eq!(stats.batches, 3);
+    assert_eq!(stats.nulls["id1"], nulls);
+}
+
+#[test]
+fn join_partitions_align_keys() {
+    let table = |name: &str, keys: Vec<i64>| {
+        RecordBatch::try_from_iter([(name, Arc::new(Int64Array::from(keys)) as ArrayRef)]).unwrap()
+    };
+    let lhs = table("id1", (0..1_000).map(|i| i % 50 + 1).collect());
+    let rhs_small = table("id1", (1..=50).collect());
+    let rhs_medium = table("id2", (1..=100).collect());
+    let parts = generate_join_family(
+        PyArrowType(lhs),
+        PyArrowType(rhs_small),
+        PyArrowType(rhs_medium.clone()),
+        PyArrowType(rhs_medium),
+        4,
+        "id1",
+    )
+    .unwrap();
+    let keys = |batch: &RecordBatch| {
+        let id1 = batch.column(0).as_primitive::<Int64Type>();
+        id1.values().iter().copied().collect::<HashSet<i64>>()
+    };
+    for (p, lhs_part) in parts.iter().enumerate() {
+        for (q, rhs_part) in parts.iter().enumerate() {
+            let common = keys(&lhs_part.0 .0)
+                .intersection(&keys(&rhs_part.1 .0))
+                .count();
+            assert_eq!(common > 0, p == q && lhs_part.0 .0.num_rows() > 0);
+        }
+        // Tables without the key are replicated
+        assert_eq!(lhs_part.2 .0.num_rows(), 100);
+    }
+}
+
+#[test]
+fn null_bias_groups_have_higher_null_rate() {
+    let batch = groupby_with_params(GroupByParams {
+        null_bias_groups: Some((1..=10).collect()),
+        null_bias_nas: 50,
+        ..params(100_000, 100, 5, 42, 10_000)
+    })
+    .unwrap();
+    let id1 = batch.column(0).as_string::<i32>();
+    let id4 = batch.column(3);
+    let (mut biased, mut other) = ((0, 0), (0, 0));
+    for i in 0..batch.num_rows() {
+        if id1.is_null(i) {
+            continue;
+        }
+        let group = id1.value(i)[2..].parse::<i64>().unwrap();
+        let counts = if group <= 10 { &mut biased } else { &mut other };
+        counts.0 += id4.is_null(i) as usize;
+        counts.1 += 1;
+    }
+    let biased = biased.0 as f64 / biased.1 as f64;
+    let other = other.0 as f64 / other.1 as f64;
+    assert!((0.4..0.6).contains(&biased), "{}", biased);
+    assert!((0.02..0.08).contains(&other), "{}", other);
+}
+
+#[test]
+fn concat_widens_int32_ids() {
+    let wide = groupby_batch(10_000, 100, 10, 1, 500, None, None).unwrap();
+    let narrow = groupby_batch(10_000, 100, 10, 2, 300, None, None).unwrap();
+    let fields = narrow
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| match field.name().as_str() {
+            "id4" | "id5" | "id6" => field.as_ref().clone().with_data_type(DataType::Int32),
+            _ => field.as_ref().clone(),
+        })
+        .collect::<Vec<Field>>();
+    let narrow = cast_batch(PyArrowType(narrow), PyArrowType(Schema::new(fields)))
+        .unwrap()
+        .0;
+    let stacked = concat_groupby_batches(vec![PyArrowType(wide.clone()), PyArrowType(narrow)])
+        .unwrap()
+        .0;
+    assert_eq!(stacked.num_rows(), 800);
+    assert_eq!(stacked.schema(), wide.schema());
+}
+
+#[test]
+fn c_data_export_reimports_losslessly() {
+    let batch = groupby_with_params(GroupByParams {
+        with_map: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let exported = export_batch_c_data(PyArrowType(batch.clone())).unwrap();
+    assert_eq!(exported.len(), batch.num_columns());
+    for ((name, array, schema), (field, column)) in exported
+        .into_iter()
+        .zip(batch.schema().fields().iter().zip(batch.columns()))
+    {
+        assert_eq!(&name, field.name());
+        // The test is the consumer, so it takes ownership of both structs
+        let (array, schema) = unsafe {
+            (
+                *Box::from_raw(array as *mut arrow::ffi::FFI_ArrowArray),
+                Box::from_raw(schema as *mut arrow::ffi::FFI_ArrowSchema),
+            )
+        };
+        let data = unsafe { arrow::ffi::from_ffi(array, &schema) }.unwrap();
+        assert_eq!(make_array(data).as_ref(), column.as_ref());
+    }
+}
+
+#[test]
+fn all_null_strings_skip_draws() {
+    let (batch, words) = consumed_words(GroupByParams {
+        nas_string: Some(100),
+        ..params(10_000, 100, 0, 42, 1_000)
+    });
+    for column in &batch.columns()[..3] {
+        assert_eq!(column.null_count(), 1_000);
+    }
+    assert_eq!(batch.column(3).null_count(), 0);
+    // No NULL checks and values are drawn for id1, id2 and id3 in the bulk path
+    let (_, words_with_values) = consumed_words(GroupByParams {
+        nas_string: Some(99),
+        ..params(10_000, 100, 0, 42, 1_000)
+    });
+    assert!(words < words_with_values);
+}
+
+#[test]
+fn id1_offset_shifts_the_key_space() {
+    let batch = groupby_with_params(GroupByParams {
+        id1_offset: 1_000,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let numbers = batch
+        .column(0)
+        .as_string::<i32>()
+        .iter()
+        .flatten()
+        .map(|value| value[2..].parse::<i64>().unwrap())
+        .collect::<Vec<i64>>();
+    assert!(numbers
+        .iter()
+        .all(|number| (1_001..=1_100).contains(number)));
+}
+
+#[test]
+fn corrupt_rate_corrupts_exact_count() {
+    let batch = groupby_with_params(GroupByParams {
+        corrupt_rate: 0.1,
+        corrupt_target: Some("v1"),
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    let corrupted = v1.values().iter().filter(|v| **v == -1_000_000_000).count();
+    assert_eq!(corrupted, 100);
+    assert!(groupby_with_params(GroupByParams {
+        corrupt_rate: 0.1,
+        corrupt_target: Some("v4"),
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .is_err());
+}
+
+#[test]
+fn cumulative_v1_grows_within_id1_blocks() {
+    let batch = groupby_with_params(GroupByParams {
+        cumulative_v1: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let id1 = batch
+        .column(0)
+        .as_string::<i32>()
+        .iter()
+        .collect::<Vec<_>>();
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    for i in 1..batch.num_rows() {
+        if id1[i] == id1[i - 1] {
+            assert!(v1.value(i) >= v1.value(i - 1));
+        }
+    }
+}
+
+#[test]
+fn expected_null_fraction_matches_sample() {
+    for (nas, exact_percent) in [(10, true), (10, false), (100, false)] {
+        let batch = groupby_with_params(GroupByParams {
+            exact_percent,
+            ..params(1_000_000, 100, nas, 42, 100_000)
+        })
+        .unwrap();
+        let expected = expected_null_fraction(nas, exact_percent).unwrap();
+        let actual = batch.column(3).null_count() as f64 / batch.num_rows() as f64;
+        assert!(
+            (expected - actual).abs() < 0.005,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn legacy_nas_keeps_some_values_at_100() {
+    let base = params(100_000, 100, 100, 42, 10_000);
+    let exact = groupby_with_params(base.clone()).unwrap();
+    for column in exact.columns().iter().take(6) {
+        assert_eq!(column.null_count(), column.len());
+    }
+    let legacy = groupby_with_params(GroupByParams {
+        exact_percent: false,
+        ..base
+    })
+    .unwrap();
+    for column in legacy.columns().iter().take(6) {
+        // nas/101 of NULLs, so about 1% of values are kept
+        let values = column.len() - column.null_count();
+        assert!((50..200).contains(&values), "{} values", values);
+    }
+}
+
+#[test]
+fn id1_is_null_selectivity_matches_null_count() {
+    let stats = groupby_predicate_stats(10_000, 100, 10, 42, 1_000)
+        .unwrap()
+        .0;
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    let predicates = stats.column(0).as_string::<i32>();
+    let matched = stats.column(1).as_primitive::<Int64Type>();
+    let selectivity = stats.column(2).as_primitive::<Float64Type>();
+    let row = predicates
+        .iter()
+        .position(|predicate| predicate == Some("id1 IS NULL"))
+        .unwrap();
+    let nulls = batch.column(0).null_count();
+    assert_eq!(matched.value(row), nulls as i64);
+    assert_eq!(selectivity.value(row), nulls as f64 / 1_000.0);
+}
+
+#[test]
+fn shared_dictionary_is_the_same_in_every_batch() {
+    let batches = GroupByReader::try_new(2_000, 100, 10, 42, 1_000, None, true, true)
+        .unwrap()
+        .collect::<Result<Vec<RecordBatch>, _>>()
+        .unwrap();
+    let dictionary = |batch: &RecordBatch| {
+        batch
+            .column(0)
+            .as_dictionary::<Int32Type>()
+            .values()
+            .clone()
+    };
+    assert_eq!(dictionary(&batches[0]).len(), 100);
+    assert_eq!(&dictionary(&batches[0]), &dictionary(&batches[1]));
+    assert_ne!(batches[0].column(0), batches[1].column(0));
+}
+
+#[test]
+fn v3_edge_rate_injects_negative_zero() {
+    let batch = groupby_with_params(GroupByParams {
+        v3_edge_rate: 0.1,
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+    let negative_zeros = v3
+        .values()
+        .iter()
+        .filter(|v| v.to_bits() == (-0.0f64).to_bits())
+        .count();
+    assert!(negative_zeros > 0);
+}
+
+#[test]
+fn ipc_file_is_valid_for_any_flush_every() {
+    let dir = test_dir("ipc");
+    for flush_every in [1, 2, 5] {
+        let path = dir.join(format!("groupby-{}.arrow", flush_every));
+        generate_groupby_to_ipc(
+            path.clone(),
+            10_000,
+            100,
+            10,
+            42,
+            1_000,
+            flush_every,
+            64,
+            "v5",
+            true,
+            false,
+        )
+        .unwrap();
+        let reader =
+            arrow::ipc::reader::FileReader::try_new(File::open(path).unwrap(), None).unwrap();
+        let rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(rows, 10_000);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn custom_schema_is_filled() {
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Int32, false),
+        Field::new("b", DataType::Utf8, true),
+        Field::new("c", DataType::Boolean, false),
+    ]);
+    let batch = generate_for_schema(PyArrowType(schema.clone()), 100, 42, 10)
+        .unwrap()
+        .0;
+    assert_eq!(batch.schema().as_ref(), &schema);
+    assert_eq!(batch.num_rows(), 100);
+    assert_eq!(batch.column(0).null_count(), 0);
+    assert!(batch.column(1).null_count() > 0);
+    let unsupported = Schema::new(vec![Field::new("d", DataType::Date32, false)]);
+    assert!(generate_for_schema(PyArrowType(unsupported), 100, 42, 0).is_err());
+}
+
+#[test]
+fn string_capacity_saturates() {
+    assert_eq!(safe_string_capacity(1_000, 8), 8_000);
+    assert_eq!(safe_string_capacity(usize::MAX / 2, 8), i32::MAX as usize);
+    assert_eq!(safe_string_capacity(1 << 40, 1 << 40), i32::MAX as usize);
+}
+
+#[test]
+fn sort_by_orders_lexicographically() {
+    let batch = groupby_with_params(GroupByParams {
+        sort_by: Some(vec!["id1".to_string(), "id2".to_string()]),
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let id1 = batch.column(0).as_string::<i32>();
+    let id2 = batch.column(1).as_string::<i32>();
+    // NULLs sort last, so they compare as greater than any value
+    let keys = id1
+        .iter()
+        .zip(id2.iter())
+        .map(|(a, b)| (a.is_none(), a, b.is_none(), b))
+        .collect::<Vec<_>>();
+    assert!(keys.is_sorted());
+}
+
+#[test]
+fn value_seed_and_key_seed_are_independent() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let default = groupby_with_params(base.clone()).unwrap();
+    for value_seed in [1, 2] {
+        let batch = groupby_with_params(GroupByParams {
+            value_seed: Some(value_seed),
+            ..base.clone()
+        })
+        .unwrap();
+        assert_eq!(batch.columns()[..6], default.columns()[..6]);
+        assert_ne!(batch.column(6), default.column(6));
+        assert_ne!(batch.column(8), default.column(8));
+    }
+    let batch = groupby_with_params(GroupByParams {
+        key_seed: Some(1),
+        ..base.clone()
+    })
+    .unwrap();
+    assert_ne!(batch.column(0), default.column(0));
+    assert_eq!(batch.columns()[6..], default.columns()[6..]);
+}
+
+#[test]
+fn byte_budget_batches_are_near_target() {
+    let target_bytes = 100_000;
+    let mut reader = GroupByByteBudgetReader::new(50_000, 100, 10, 42, target_bytes).unwrap();
+    let mut sizes = Vec::new();
+    while let Some(batch) = reader.__next__().unwrap() {
+        sizes.push((batch.0.num_rows(), estimated_batch_bytes(&batch.0).unwrap()));
+    }
+    assert_eq!(sizes.iter().map(|(rows, _)| rows).sum::<usize>(), 50_000);
+    // The last batch covers the remainder, so it may be smaller
+    for (_, bytes) in &sizes[..sizes.len() - 1] {
+        let ratio = *bytes as f64 / target_bytes as f64;
+        assert!((0.9..1.1).contains(&ratio), "{} bytes", bytes);
+    }
+}
+
+#[test]
+fn int_measure_switches_v2_to_int64() {
+    let rhs = generate_join_rhs_small(
+        1_000,
+        42,
+        join_keys(1..=100),
+        100,
+        false,
+        false,
+        true,
+        false,
+        1.0,
+        100.0,
+        "uniform",
+    )
+    .unwrap()
+    .0;
+    let v2 = rhs.column_by_name("v2").unwrap();
+    assert_eq!(v2.data_type(), &DataType::Int64);
+    let v2 = v2.as_primitive::<Int64Type>();
+    assert!(v2.values().iter().all(|v| (1..=100).contains(v)));
+}
+
+#[cfg(unix)]
+#[test]
+fn shm_segment_is_readable_by_another_mapping() {
+    let name = format!("/falsa-test-{}", std::process::id());
+    let (os_id, size) = generate_groupby_to_shm(&name, 2_500, 100, 10, 42, 1_000).unwrap();
+    let mut segment = ShmemConf::new().os_id(&os_id).open().unwrap();
+    // The reader owns the segment, so it is unlinked when the mapping is dropped
+    segment.set_owner(true);
+    let bytes = unsafe { segment.as_slice() }[..size].to_vec();
+    let reader =
+        arrow::ipc::reader::FileReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+    let rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+    assert_eq!(rows, 2_500);
+}
+
+#[test]
+fn permutation_is_valid_and_reproducible() {
+    let permutation = deterministic_permutation(1_000, 42).unwrap().0;
+    let mut indices = Int64Array::from(permutation.clone()).values().to_vec();
+    assert_ne!(indices, (0..1_000).collect::<Vec<i64>>());
+    indices.sort();
+    assert_eq!(indices, (0..1_000).collect::<Vec<i64>>());
+    assert_eq!(deterministic_permutation(1_000, 42).unwrap().0, permutation);
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn zstd_levels_give_valid_files_of_different_sizes() {
+    let dir = test_dir("zstd");
+    let write = |level| {
+        let path = dir.join(format!("groupby-{}.parquet", level));
+        generate_groupby_to_parquet(
+            path.clone(),
+            10_000,
+            100,
+            10,
+            42,
+            10_000,
+            1,
+            "zstd",
+            Some(level),
+            false,
+            false,
+        )
+        .unwrap();
+        let rows = read_parquet(&path)
+            .iter()
+            .map(|b| b.num_rows())
+            .sum::<usize>();
+        assert_eq!(rows, 10_000);
+        std::fs::metadata(path).unwrap().len()
+    };
+    assert_ne!(write(1), write(19));
+    assert!(parquet_compression("zstd", Some(23)).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn shards_give_contiguous_global_sequence() {
+    let mut id0 = Vec::new();
+    for shard_index in 0..3 {
+        let shard = generate_groupby_shard(1_000, 10, 5, 42, 3, shard_index, true)
+            .unwrap()
+            .0;
+        let column = shard.column_by_name("id0").unwrap();
+        id0.extend_from_slice(column.as_primitive::<Int64Type>().values());
+    }
+    assert_eq!(id0, (0..1_000).collect::<Vec<i64>>());
+}
+
+#[test]
+fn row_groups_are_sorted_by_id1() {
+    let dir = test_dir("rowgroup_sorted");
+    let path = dir.join("groupby.parquet");
+    generate_groupby_to_parquet(
+        path.clone(),
+        3_000,
+        100,
+        10,
+        42,
+        1_000,
+        1,
+        "uncompressed",
+        None,
+        true,
+        false,
+    )
+    .unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(builder.metadata().num_row_groups(), 3);
+    for row_group in 0..3 {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+        let batches = builder
+            .with_row_groups(vec![row_group])
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<RecordBatch>, _>>()
+            .unwrap();
+        let id1 = batches
+            .iter()
+            .flat_map(|batch| batch.column(0).as_string::<i32>().iter())
+            .map(|value| (value.is_none(), value.map(str::to_string)))
+            .collect::<Vec<_>>();
+        assert_eq!(id1.len(), 1_000);
+        assert!(id1.is_sorted());
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn id1_cardinality_is_at_most_k() {
+    let cardinalities = groupby_cardinalities(100_000, 10, 10, 42, 1_000).unwrap().0;
+    let names = cardinalities.column(0).as_string::<i32>();
+    let counts = cardinalities.column(1).as_primitive::<Int64Type>();
+    let row = names.iter().position(|name| name == Some("id1")).unwrap();
+    assert!((1..=10).contains(&counts.value(row)));
+}
+
+#[test]
+fn binary_id3_holds_utf8_bytes() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let default = groupby_with_params(base.clone()).unwrap();
+    let batch = groupby_with_params(GroupByParams {
+        binary_id3: true,
+        ..base
+    })
+    .unwrap();
+    let id3 = batch.column(2);
+    assert_eq!(id3.data_type(), &DataType::Binary);
+    let decoded = id3
+        .as_binary::<i32>()
+        .iter()
+        .map(|value| value.map(|bytes| std::str::from_utf8(bytes).unwrap()))
+        .collect::<StringArray>();
+    assert_eq!(&decoded, default.column(2).as_string::<i32>());
+}
+
+#[test]
+fn id4_repeats_in_runs() {
+    let batch = groupby_with_params(GroupByParams {
+        run_length: 4,
+        ..params(10_000, 100, 10, 42, 1_002)
+    })
+    .unwrap();
+    let id4 = batch
+        .column(3)
+        .as_primitive::<Int64Type>()
+        .iter()
+        .collect::<Vec<_>>();
+    // The last run is cut by the end of the batch
+    for run in id4.chunks(4) {
+        assert!(run.iter().all(|value| *value == run[0]));
+    }
+    assert!(id4
+        .chunks(4)
+        .zip(id4.chunks(4).skip(1))
+        .any(|(a, b)| a[0] != b[0]));
+}
+
+#[test]
+fn value_column_matches_the_batch() {
+    for (kind, index) in [("v1", 6), ("v2", 7), ("v3", 8)] {
+        let column = make_array(generate_value_column(kind, 1_000, 7, None).unwrap().0);
+        let batch = groupby_with_params(GroupByParams {
+            column_seeds: Some([(kind.to_string(), 7)].into_iter().collect()),
+            ..params(10_000, 100, 10, 42, 1_000)
+        })
+        .unwrap();
+        assert_eq!(&column, batch.column(index));
+    }
+}
+
+#[test]
+fn v3_collision_levels_bound_distinct_values() {
+    let batch = groupby_with_params(GroupByParams {
+        v3_collision_levels: Some(7),
+        ..params(10_000, 100, 0, 42, 1_000)
+    })
+    .unwrap();
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+    let distinct = v3
+        .values()
+        .iter()
+        .map(|v| v.to_bits())
+        .collect::<HashSet<u64>>();
+    assert!(distinct.len() <= 7);
+}
+
+#[test]
+fn split_batch_slices_concatenate_back() {
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    let parts = split_batch(PyArrowType(batch.clone()), 3)
+        .unwrap()
+        .into_iter()
+        .map(|part| part.0)
+        .collect::<Vec<RecordBatch>>();
+    let lengths = parts
+        .iter()
+        .map(|part| part.num_rows())
+        .collect::<Vec<usize>>();
+    assert_eq!(lengths, [334, 333, 333]);
+    assert_eq!(concat_batches(&batch.schema(), &parts).unwrap(), batch);
+}
+
+#[test]
+fn seasonal_keeps_edge_values() {
+    let base = GroupByParams {
+        monotonic_ts: true,
+        v3_edge_rate: 0.3,
+        ..params(10_000, 100, 0, 42, 1_000)
+    };
+    let plain = groupby_with_params(base.clone()).unwrap();
+    let seasonal = groupby_with_params(GroupByParams {
+        seasonal: true,
+        ..base
+    })
+    .unwrap();
+    let plain = plain.column(8).as_primitive::<Float64Type>();
+    let seasonal = seasonal.column(8).as_primitive::<Float64Type>();
+    let mut edges = 0;
+    for (plain, seasonal) in plain.values().iter().zip(seasonal.values()) {
+        if V3_EDGE_VALUES
+            .iter()
+            .any(|e| e.to_bits() == plain.to_bits())
+        {
+            assert_eq!(plain.to_bits(), seasonal.to_bits());
+            edges += 1;
+        }
+    }
+    assert!(edges > 0);
+}
+
+#[test]
+fn seasonal_is_rejected_with_collision_levels() {
+    let base = GroupByParams {
+        monotonic_ts: true,
+        seasonal: true,
+        ..params(10_000, 100, 0, 42, 1_000)
+    };
+    assert!(groupby_with_params(GroupByParams {
+        v3_collision_levels: Some(4),
+        ..base.clone()
+    })
+    .is_err());
+    // 1970-01-01 is Thursday, so day 2 is Saturday and day 3 is Sunday
+    let means = (0..7)
+        .map(|day| {
+            let batch = groupby_with_params(GroupByParams {
+                ts_start: day * 86_400_000_000,
+                ..base.clone()
+            })
+            .unwrap();
+            let v3 = batch.column(8).as_primitive::<Float64Type>();
+            v3.values().iter().sum::<f64>() / v3.len() as f64
+        })
+        .collect::<Vec<f64>>();
+    let weekend = means[2].max(means[3]);
+    let weekday = [0, 1, 4, 5, 6].map(|day| means[day]);
+    assert!(weekday.iter().all(|mean| *mean > weekend), "{:?}", means);
+}
+
+#[test]
+fn disjoint_rhs_keys_miss_the_lhs_key_space() {
+    let levels = join_key_levels(10_000_000 / 1_000_000);
+    let rhs = generate_join_rhs_small(
+        10_000_000,
+        42,
+        join_keys((0..100).map(|i| i % levels + 1)),
+        100,
+        false,
+        false,
+        false,
+        true,
+        1.0,
+        100.0,
+        "uniform",
+    )
+    .unwrap()
+    .0;
+    let id1 = rhs
+        .column_by_name("id1")
+        .unwrap()
+        .as_primitive::<Int64Type>();
+    assert!(id1.values().iter().all(|key| !(1..=levels).contains(key)));
+    assert!(id1
+        .values()
+        .iter()
+        .all(|key| (levels + 1..=2 * levels).contains(key)));
+}
+
+#[test]
+fn column_order_reorders_the_schema() {
+    let order = ["v3", "id1", "v1", "id6", "id2", "v2", "id3", "id5", "id4"].map(String::from);
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let batch = groupby_with_params(GroupByParams {
+        column_order: Some(order.to_vec()),
+        ..base.clone()
+    })
+    .unwrap();
+    let names = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(names, order);
+    let default = groupby_with_params(base.clone()).unwrap();
+    for name in &order {
+        assert_eq!(batch.column_by_name(name), default.column_by_name(name));
+    }
+    assert!(groupby_with_params(GroupByParams {
+        column_order: Some(vec!["id1".to_string(), "v1".to_string()]),
+        ..base
+    })
+    .is_err());
+}
+
+#[test]
+fn numeric_bytes_have_rows_times_element_size() {
+    let batch = groupby_with_params(GroupByParams {
+        float16: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let columns = numeric_column_bytes(PyArrowType(batch.clone()), "little").unwrap();
+    let names = columns
+        .iter()
+        .map(|(name, _, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, ["id4", "id5", "id6", "v1", "v2", "v3"]);
+    for (name, _, bytes) in &columns {
+        let column = batch.column_by_name(name).unwrap();
+        let size = column.data_type().primitive_width().unwrap();
+        assert_eq!(bytes.len(), batch.num_rows() * size);
+    }
+}
+
+#[test]
+fn tight_memory_budget_returns_every_row() {
+    let base = params(10_000, 100, 10, 42, 10_000);
+    let batch = groupby_with_params(GroupByParams {
+        max_memory_bytes: Some(10_000),
+        ..base.clone()
+    })
+    .unwrap();
+    let default = groupby_with_params(base).unwrap();
+    assert_eq!(batch.num_rows(), 10_000);
+    assert_eq!(batch.schema(), default.schema());
+    // Sub-batches use derived seeds, so the batch is assembled from several of them
+    assert_ne!(batch, default);
+    let v1 = batch
+        .column_by_name("v1")
+        .unwrap()
+        .as_primitive::<Int64Type>();
+    assert!(v1.values().iter().all(|v| (1..=5).contains(v)));
+    assert!(batch.column_by_name("id4").unwrap().null_count() > 0);
+}
+
+#[test]
+fn mismatched_join_keys_are_described() {
+    let lhs = Schema::new(vec![
+        Field::new("id1", DataType::Int64, false),
+        Field::new("id4", DataType::Utf8, false),
+        Field::new("v1", DataType::Float64, false),
+    ]);
+    let rhs = Schema::new(vec![
+        Field::new("id1", DataType::Int64, false),
+        Field::new("id4", DataType::Int64, false),
+        Field::new("id7", DataType::Utf8, false),
+        Field::new("v2", DataType::Int64, false),
+    ]);
+    assert_eq!(
+        diff_join_schemas(PyArrowType(lhs.clone()), PyArrowType(rhs)),
+        [
+            "id4 is Utf8 in lhs but Int64 in rhs",
+            "id7 is in rhs but not in lhs",
+        ]
+    );
+    assert!(diff_join_schemas(PyArrowType(lhs.clone()), PyArrowType(lhs)).is_empty());
+}
+
+#[test]
+fn index_hashed_keys_do_not_depend_on_preceding_rows() {
+    let row = 500;
+    let full = groupby_with_params(GroupByParams {
+        index_hashed_keys: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    // Optional columns consume the main stream, which should not shift the keys
+    let alone = groupby_with_params(GroupByParams {
+        index_hashed_keys: true,
+        with_map: true,
+        blob_bytes: 8,
+        ..params(10_000, 100, 10, 42, row as i64 + 1)
+    })
+    .unwrap();
+    assert_eq!(full.slice(0, row + 1).columns()[..6], alone.columns()[..6]);
+}
+
+#[test]
+fn index_hashed_keys_ignore_with_offset_padding() {
+    let base = GroupByParams {
+        index_hashed_keys: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    };
+    let plain = groupby_with_params(base.clone()).unwrap();
+    let padded = groupby_with_params(GroupByParams {
+        with_offset: true,
+        ..base
+    })
+    .unwrap();
+    assert_eq!(plain.columns()[..6], padded.columns()[..6]);
+}
+
+#[test]
+fn star_schema_foreign_keys_resolve() {
+    let (facts, dims) = generate_star_schema(10_000, vec![10, 100, 1_000], 42, 3_000).unwrap();
+    assert_eq!(
+        facts.iter().map(|batch| batch.0.num_rows()).sum::<usize>(),
+        10_000
+    );
+    for (i, dim) in dims.iter().enumerate() {
+        let keys = dim.0.column(0).as_primitive::<Int64Type>();
+        let keys = keys.values().iter().collect::<HashSet<&i64>>();
+        assert_eq!(keys.len(), dim.0.num_rows());
+        for fact in &facts {
+            let fk = fact.0.column_by_name(&format!("fk{}", i)).unwrap();
+            let fk = fk.as_primitive::<Int64Type>();
+            assert!(fk.values().iter().all(|key| keys.contains(key)));
+        }
+    }
+}
+
+#[test]
+fn ipc_write_options_are_readable() {
+    let dir = test_dir("ipc-options");
+    let read = |alignment, metadata_version, continuation_marker| {
+        let path = dir.join(format!(
+            "groupby-{}-{}-{}.arrow",
+            alignment, metadata_version, continuation_marker
+        ));
+        generate_groupby_to_ipc(
+            path.clone(),
+            10_000,
+            100,
+            10,
+            42,
+            1_000,
+            1,
+            alignment,
+            metadata_version,
+            continuation_marker,
+            false,
+        )
+        .unwrap();
+        let reader =
+            arrow::ipc::reader::FileReader::try_new(File::open(path).unwrap(), None).unwrap();
+        reader
+            .map(|batch| batch.unwrap())
+            .collect::<Vec<RecordBatch>>()
+    };
+    let expected = read(64, "v5", true);
+    assert_eq!(read(8, "v5", true), expected);
+    assert_eq!(read(32, "v5", true), expected);
+    assert_eq!(read(16, "v4", false), expected);
+    // The alignment should be one of 8, 16, 32, 64 and the legacy format requires v4
+    assert!(generate_groupby_to_ipc(
+        dir.join("a.arrow"),
+        100,
+        10,
+        0,
+        42,
+        100,
+        1,
+        3,
+        "v5",
+        true,
+        false
+    )
+    .is_err());
+    assert!(generate_groupby_to_ipc(
+        dir.join("b.arrow"),
+        100,
+        10,
+        0,
+        42,
+        100,
+        1,
+        64,
+        "v5",
+        false,
+        false
+    )
+    .is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn range_matches_slice_of_full_batch() {
+    for nas in [0, 10] {
+        let full = groupby_batch(10_000, 100, nas, 42, 1_000, None, None).unwrap();
+        let range = generate_groupby_range(10_000, 100, nas, 42, 100, 100, 1_000)
+            .unwrap()
+            .0;
+        assert_eq!(range, full.slice(100, 100));
+    }
+    assert!(generate_groupby_range(10_000, 100, 0, 42, 950, 100, 1_000).is_err());
+}
+
+#[test]
+fn decimal256_v3_keeps_the_magnitude() {
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let batch = groupby_with_params(GroupByParams {
+        decimal256: true,
+        ..base.clone()
+    })
+    .unwrap();
+    let v3 = batch.column_by_name("v3").unwrap();
+    assert_eq!(v3.data_type(), &DataType::Decimal256(40, 4));
+    let default = groupby_with_params(base).unwrap();
+    let expected = default
+        .column_by_name("v3")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let v3 = v3.as_primitive::<arrow::datatypes::Decimal256Type>();
+    for (value, expected) in v3.values().iter().zip(expected.values()) {
+        let value = value.to_i128().unwrap() as f64 / 10_000.0;
+        assert!(
+            (value - expected).abs() <= 0.5e-4,
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+}
+
+#[test]
+fn sidecar_rows_match_the_file() {
+    let dir = test_dir("sidecar");
+    let path = dir.join("groupby.parquet");
+    let rows = generate_groupby_to_parquet(
+        path.clone(),
+        10_000,
+        100,
+        10,
+        42,
+        3_000,
+        1,
+        "uncompressed",
+        None,
+        false,
+        true,
+    )
+    .unwrap();
+    let sidecar = std::fs::read_to_string(dir.join("groupby.parquet.meta.json")).unwrap();
+    let meta = serde_json::from_str::<serde_json::Value>(&sidecar).unwrap();
+    let batches = read_parquet(&path);
+    let file_rows = batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+    assert_eq!(meta["rows"], json!(file_rows));
+    assert_eq!(rows as usize, file_rows);
+    assert_eq!(meta["seed"], json!(42));
+    let id1_nulls = batches
+        .iter()
+        .map(|batch| batch.column(0).null_count())
+        .sum::<usize>();
+    assert_eq!(meta["null_counts"]["id1"], json!(id1_nulls));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn with_offset_slices_padded_rows() {
+    let batch = groupby_with_params(GroupByParams {
+        with_offset: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    assert_eq!(batch.num_rows(), 1_000);
+    let padded = groupby_with_params(params(10_000, 100, 10, 42, 1_003)).unwrap();
+    assert_eq!(batch, padded.slice(3, 1_000));
+    // Value buffers are sliced in place, validity and string offsets keep the padding
+    let id1 = batch.column(0).as_string::<i32>();
+    assert_ne!(id1.value_offsets()[0], 0);
+    assert_eq!(id1.nulls().unwrap().offset(), 3);
+}
+
+#[test]
+fn join_measures_respect_custom_range() {
+    for measure_dist in ["uniform", "normal"] {
+        let rhs = generate_join_rhs_small(
+            1_000,
+            42,
+            join_keys(1..=100),
+            100,
+            false,
+            false,
+            false,
+            false,
+            -5.0,
+            5.0,
+            measure_dist,
+        )
+        .unwrap()
+        .0;
+        let v2 = rhs
+            .column_by_name("v2")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert!(v2.values().iter().all(|v| (-5.0..=5.0).contains(v)));
+    }
+    let invalid = generate_join_rhs_small(
+        1_000,
+        42,
+        join_keys(1..=100),
+        100,
+        false,
+        false,
+        false,
+        false,
+        5.0,
+        -5.0,
+        "uniform",
+    );
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn filter_returns_only_matching_rows() {
+    let base = params(10_000, 100, 0, 42, 1_000);
+    let batch = groupby_with_params(GroupByParams {
+        filter: Some("v1 = 3"),
+        ..base.clone()
+    })
+    .unwrap();
+    assert!(batch.num_rows() > 0);
+    let v1 = batch.column(6).as_primitive::<Int64Type>();
+    assert!(v1.values().iter().all(|v| *v == 3));
+    let batch = groupby_with_params(GroupByParams {
+        filter: Some("v3 > 50"),
+        ..base.clone()
+    })
+    .unwrap();
+    assert!(batch.num_rows() > 0);
+    let v3 = batch.column(8).as_primitive::<Float64Type>();
+    assert!(v3.values().iter().all(|v| *v > 50.0));
+    assert!(groupby_with_params(GroupByParams {
+        filter: Some("v1 ~ 3"),
+        ..base
+    })
+    .is_err());
+}
+
+#[test]
+fn row_hash_matches_other_columns() {
+    let base = GroupByParams {
+        with_checksum: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    };
+    let batch = groupby_with_params(base.clone()).unwrap();
+    let last = batch.num_columns() - 1;
+    let others = batch.project(&(0..last).collect::<Vec<usize>>()).unwrap();
+    assert_eq!(
+        &(Arc::new(row_checksums(&others).unwrap()) as ArrayRef),
+        batch.column(last)
+    );
+    let batch = groupby_with_params(GroupByParams {
+        force_nullable: true,
+        ..base
+    })
+    .unwrap();
+    assert!(batch
+        .schema()
+        .fields()
+        .iter()
+        .all(|field| field.is_nullable()));
+}
+
+#[test]
+fn compressibility_shrinks_gzip_size() {
+    let gzip_size = |target_compressibility| {
+        let batch = groupby_with_params(GroupByParams {
+            target_compressibility,
+            ..params(10_000, 100, 10, 42, 10_000)
+        })
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::GZIP(Default::default()))
+            .build();
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        buffer.len()
+    };
+    let sizes = [0.0, 0.5, 0.9].map(gzip_size);
+    assert!(sizes[0] > sizes[1] && sizes[1] > sizes[2], "{:?}", sizes);
+    assert!(groupby_with_params(GroupByParams {
+        target_compressibility: 1.5,
+        ..params(10_000, 100, 10, 42, 10_000)
+    })
+    .is_err());
+}
+
+#[test]
+fn mixed_workload_reader_alternates_schemas() {
+    let mut reader = MixedWorkloadReader::new(1_000, 10, 0, 42, 300).unwrap();
+    let mut names = Vec::new();
+    while let Some((name, batch)) = reader.__next__().unwrap() {
+        // id1 is a string key in group-by batches and an integer key in join ones
+        let expected = match name.as_str() {
+            "groupby" => DataType::Utf8,
+            _ => DataType::Int64,
+        };
+        assert_eq!(batch.0.schema().field(0).data_type(), &expected);
+        names.push(name);
+    }
+    let expected = ["groupby", "join_lhs"].repeat(4);
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn expected_join_rows_per_join_type() {
+    // Keys 1 and 2 match 2 x 1 and 1 x 2 rows, 3 and 5 are unmatched, NULLs never match
+    let lhs = RecordBatch::try_from_iter([(
+        "id1",
+        Arc::new(Int64Array::from(vec![
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(3),
+            None,
+        ])) as ArrayRef,
+    )])
+    .unwrap();
+    let rhs = RecordBatch::try_from_iter([(
+        "id1",
+        Arc::new(Int64Array::from(vec![
+            Some(1),
+            Some(2),
+            Some(2),
+            Some(5),
+            None,
+        ])) as ArrayRef,
+    )])
+    .unwrap();
+    for (join_type, expected) in [("inner", 4), ("left", 6), ("right", 6), ("outer", 8)] {
+        let rows = expected_join_rows(
+            PyArrowType(lhs.clone()),
+            PyArrowType(rhs.clone()),
+            "id1",
+            join_type,
+        )
+        .unwrap();
+        assert_eq!(rows, expected, "{}", join_type);
+    }
+    assert!(expected_join_rows(
+        PyArrowType(lhs.clone()),
+        PyArrowType(rhs.clone()),
+        "id1",
+        "cross"
+    )
+    .is_err());
+    assert!(expected_join_rows(PyArrowType(lhs), PyArrowType(rhs), "id2", "inner").is_err());
+}
+
+#[test]
+fn field_metadata_is_read_back() {
+    let metadata = HashMap::from([(
+        "v3".to_string(),
+        HashMap::from([
+            ("unit".to_string(), "usd".to_string()),
+            ("description".to_string(), "price".to_string()),
+        ]),
+    )]);
+    let base = params(10_000, 100, 10, 42, 1_000);
+    let batch = groupby_with_params(GroupByParams {
+        field_metadata: Some(metadata.clone()),
+        ..base.clone()
+    })
+    .unwrap();
+    let schema = batch.schema();
+    assert_eq!(
+        schema.field_with_name("v3").unwrap().metadata(),
+        &metadata["v3"]
+    );
+    assert!(schema.field_with_name("v1").unwrap().metadata().is_empty());
+    assert!(groupby_with_params(GroupByParams {
+        field_metadata: Some(HashMap::from([("v9".to_string(), HashMap::new())])),
+        ..base
+    })
+    .is_err());
+}
+
+#[test]
+fn messy_strings_follow_messy_rate() {
+    let messy_fraction = |messy_strings| {
+        let batch = groupby_with_params(GroupByParams {
+            messy_strings,
+            messy_rate: 0.2,
+            ..params(10_000, 100, 10, 42, 10_000)
+        })
+        .unwrap();
+        let id1 = batch.column(0).as_string::<i32>();
+        let messy = id1
+            .iter()
+            .flatten()
+            .filter(|value| value.trim() != *value || value.starts_with("ID"))
+            .count();
+        messy as f64 / (id1.len() - id1.null_count()) as f64
+    };
+    let fraction = messy_fraction(true);
+    assert!((fraction - 0.2).abs() < 0.02, "{}", fraction);
+    assert_eq!(messy_fraction(false), 0.0);
+}
+
+#[test]
+fn object_store_output_reads_back() {
+    let store = Arc::new(object_store::memory::InMemory::new());
+    let path = object_store::path::Path::from("groupby.parquet");
+    let rows = write_groupby_to_store(store.clone(), path.clone(), 1_000, 10, 5, 42, 300).unwrap();
+    assert_eq!(rows, 1_000);
+    let bytes = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async { store.get(&path).await.unwrap().bytes().await.unwrap() });
+    let written = ParquetRecordBatchReaderBuilder::try_new(bytes)
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<Vec<RecordBatch>, _>>()
+        .unwrap();
+    let expected = GroupByReader::try_new(1_000, 10, 5, 42, 300, None, true, false)
+        .unwrap()
+        .collect::<Result<Vec<RecordBatch>, _>>()
+        .unwrap();
+    let written = concat_batches(&written[0].schema(), &written).unwrap();
+    let expected = concat_batches(&expected[0].schema(), &expected).unwrap();
+    assert_eq!(written.columns(), expected.columns());
+}
+
+#[test]
+fn group_sizes_are_exact() {
+    let group_sizes = vec![0, 1, 2, 3, 994];
+    let base = GroupByParams {
+        group_sizes: Some(group_sizes.clone()),
+        ..params(1_000, 5, 10, 42, 1_000)
+    };
+    let batch = groupby_with_params(base.clone()).unwrap();
+    let mut counts = HashMap::new();
+    for id1 in batch.column(0).as_string::<i32>().iter() {
+        *counts.entry(id1.unwrap().to_string()).or_insert(0) += 1;
+    }
+    for (group, size) in group_sizes.iter().enumerate() {
+        let count = counts.get(&format!("id{:03}", group + 1)).copied();
+        assert_eq!(count.unwrap_or(0), *size);
+    }
+    // There are no id1 groups when all the strings are NULL
+    assert!(groupby_with_params(GroupByParams { nas: 100, ..base }).is_err());
+}
+
+#[test]
+fn group_sizes_rejects_options_changing_groups() {
+    let base = GroupByParams {
+        group_sizes: Some(vec![200; 5]),
+        ..params(1_000, 5, 0, 42, 1_000)
+    };
+    let rejected = [
+        (
+            "target_compressibility",
+            GroupByParams {
+                target_compressibility: 0.5,
+                ..base.clone()
+            },
+        ),
+        (
+            "messy_strings",
+            GroupByParams {
+                messy_strings: true,
+                ..base.clone()
+            },
+        ),
+        (
+            "corrupt_target",
+            GroupByParams {
+                corrupt_rate: 0.1,
+                corrupt_target: Some("id1"),
+                ..base.clone()
+            },
+        ),
+        (
+            "filter",
+            GroupByParams {
+                filter: Some("v1 = 3"),
+                ..base.clone()
+            },
+        ),
+    ];
+    for (name, params) in rejected {
+        assert!(groupby_with_params(params).is_err(), "{}", name);
+    }
+    // Corrupting other columns keeps the sizes of id1 groups
+    assert!(groupby_with_params(GroupByParams {
+        corrupt_rate: 0.1,
+        corrupt_target: Some("v1"),
+        ..base
+    })
+    .is_ok());
+}
+
+#[test]
+fn values_only_keeps_the_keys() {
+    let batch = groupby_batch(10_000, 100, 10, 42, 1_000, None, None).unwrap();
+    let regenerated = generate_groupby_values_only(PyArrowType(batch.clone()), 7, None)
+        .unwrap()
+        .0;
+    assert_eq!(regenerated.schema(), batch.schema());
+    assert_eq!(regenerated.columns()[..6], batch.columns()[..6]);
+    for index in 6..9 {
+        assert_ne!(regenerated.column(index), batch.column(index));
+    }
+}
+
+#[test]
+fn big_endian_bytes_are_swapped() {
+    let batch = RecordBatch::try_from_iter([(
+        "v",
+        Arc::new(Int32Array::from(vec![0x01020304])) as ArrayRef,
+    )])
+    .unwrap();
+    let bytes = |endian| {
+        numeric_column_bytes(PyArrowType(batch.clone()), endian)
+            .unwrap()
+            .remove(0)
+            .2
+    };
+    assert_eq!(bytes("little"), [4, 3, 2, 1]);
+    assert_eq!(bytes("big"), [1, 2, 3, 4]);
+    assert!(numeric_column_bytes(PyArrowType(batch.clone()), "middle").is_err());
+}
+
+#[test]
+fn string_list_has_empty_and_null_lists() {
+    let batch = groupby_with_params(GroupByParams {
+        with_string_list: true,
+        ..params(10_000, 100, 10, 42, 1_000)
+    })
+    .unwrap();
+    let tags = batch.column_by_name("tags").unwrap();
+    assert_eq!(
+        tags.data_type(),
+        &DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true)))
+    );
+    let tags = tags.as_list::<i32>();
+    let empty = (0..tags.len())
+        .filter(|i| tags.is_valid(*i) && tags.value_length(*i) == 0)
+        .count();
+    assert!(empty > 0);
+    assert!(tags.null_count() > 0);
+    assert!((0..tags.len()).all(|i| tags.value_length(i) <= 3));
+}
+
+#[test]
+fn filter_with_offset_keeps_every_matching_row() {
+    let base = GroupByParams {
+        with_offset: true,
+        ..params(10_000, 100, 0, 42, 1_000)
+    };
+    let all = groupby_with_params(base.clone()).unwrap();
+    let matching = all
+        .column(6)
+        .as_primitive::<Int64Type>()
+        .values()
+        .iter()
+        .filter(|v| **v == 3)
+        .count();
+    let filtered = groupby_with_params(GroupByParams {
+        filter: Some("v1 = 3"),
+        ..base.clone()
+    })
+    .unwrap();
+    assert_eq!(filtered.num_rows(), matching);
+    // Fewer matching rows than padding ones
+    let filtered = groupby_with_params(GroupByParams {
+        filter: Some("v1 > 100"),
+        ..base
+    })
+    .unwrap();
+    assert_eq!(filtered.num_rows(), 0);
+}
@@ -3,6 +3,9 @@ import os
 import sys
 from subprocess import PIPE, STDOUT, Popen
 
+import numpy as np
+from falsa.local_fs import GroupByGenerator, JoinSmallGenerator
+
 SHA256SUM_CSV_SMALL = "bab41ac749ed0d884a89b9b87869c7ce1d41ce8b8eb48819be06da8c274b5a04"
 CSV_FILE_NAME = "G1_1e7_1e7_100_0.csv"
 
@@ -44,4 +47,36 @@ if __name__ == "__main__":
         sys.stdout.flush()
         sys.exit(1)
 
+    # Test zero-based join keys: the key space starts from 0
+    join_small = JoinSmallGenerator(10_000_000, 10, 10, 0, batch_size=10, zero_based_keys=True)
+    keys = join_small.generate_keys(1_000)
+    all_keys = np.concatenate([keys["x"], keys["l"], keys["r"]])
+    if all_keys.min() != 0:
+        sys.stdout.write(f"Wrong minimal zero-based key. Expected 0 but got {all_keys.min()}")
+        sys.stdout.flush()
+        sys.exit(1)
+
+    # Test own seeds of columns: batches do not repeat the values of the first one
+    groupby = GroupByGenerator(10_000, 100, batch_size=5_000, key_seed=1, value_seed=2)
+    first, second = groupby.iter_batches()
+    for name in ["id4", "v1"]:
+        if first.column(name).equals(second.column(name)):
+            sys.stdout.write(f"Batches have the same {name} with own seeds")
+            sys.stdout.flush()
+            sys.exit(1)
+
+    groupby = GroupByGenerator(10_000, 100, batch_size=5_000, column_seeds={"id1": 7})
+    first, second = groupby.iter_batches()
+    if first.column("id1").equals(second.column("id1")):
+        sys.stdout.write("Batches have the same id1 with column_seeds")
+        sys.stdout.flush()
+        sys.exit(1)
+
+    # Test monotonic timestamps: batches without rows after filter are skipped over
+    groupby = GroupByGenerator(10_000, 100, batch_size=5_000, monotonic_ts=True, filter="v1 > 100")
+    if sum(rb.num_rows for rb in groupby.iter_batches()) != 0:
+        sys.stdout.write("Filter v1 > 100 kept some rows")
+        sys.stdout.flush()
+        sys.exit(1)
+
     sys.exit(0)